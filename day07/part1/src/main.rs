@@ -1,52 +1,15 @@
-#![allow(clippy::cast_possible_truncation)]
-#![allow(clippy::cast_sign_loss)]
+use intcode::phase;
 
 fn main() {
-    // Read file and split by commas into vector of integers
-    let file = match std::fs::read_to_string("input") {
-        Ok(i) => i.trim().to_string(),
+    let data = match intcode::program::load("input") {
+        Ok(p) => p,
         Err(e) => panic!("Error reading input {}", e),
     };
 
-    let mut data = Vec::new();
-
-    for val in file.split(',') {
-        data.push(match val.parse::<i64>() {
-            Ok(v) => v,
-            Err(e) => panic!("Error parsing {}: {}", val, e),
-        });
-    }
-
-    let mut max = i64::min_value();
-
-    for perm in all_permutations(&mut [0, 1, 2, 3, 4], 5) {
-        let mut output = vec![0];
-
-        for phase in perm {
-            let mut amp = part1::Computer::new(data.clone());
-            output = amp.run(&[phase, output[0]]);
-            max = max.max(output[0]);
-        }
-    }
-
-    println!("{}", max);
-}
-
-fn all_permutations(v: &mut [i64], k: usize) -> Vec<Vec<i64>> {
-    if k == 1 {
-        return vec![Vec::from(v)];
+    let template = intcode::Computer::new(data);
+    match phase::best_phases(&template, &[0, 1, 2, 3, 4], 5, false) {
+        Ok(Some((_, signal))) => println!("{}", signal),
+        Ok(None) => println!("No signal"),
+        Err(e) => panic!("Error running program: {}", e),
     }
-    let mut vv = all_permutations(v, k - 1);
-
-    for i in 0..k - 1 {
-        if k % 2 == 0 {
-            v.swap(i, k - 1);
-        } else {
-            v.swap(0, k - 1);
-        }
-        let mut v2 = all_permutations(v, k - 1);
-        vv.append(&mut v2);
-    }
-
-    vv
 }
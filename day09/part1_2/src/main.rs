@@ -1,44 +1,57 @@
-#![allow(clippy::cast_possible_truncation)]
-#![allow(clippy::cast_sign_loss)]
-
+use std::collections::VecDeque;
 use std::io::{self, prelude::*};
 
+// usage: part1 [--ascii]
+// `--ascii` plays the program interactively, typing lines as ASCII input and printing ASCII output as text
 fn main() {
+    let ascii = match std::env::args().nth(1).as_deref() {
+        None => false,
+        Some("--ascii") => true,
+        Some(arg) => panic!("Unexpected argument {}", arg),
+    };
+
+    if ascii {
+        let mut c = intcode::Computer::new(read_program());
+        match c.run_io(&mut intcode::AsciiStdin::new(), &mut intcode::AsciiStdout) {
+            Ok(intcode::RunState::Halted) => {}
+            Ok(_) => eprintln!("\nInput closed before program halted"),
+            Err(e) => panic!("Error running program: {}", e),
+        }
+        if let Err(e) = io::stdout().flush() {
+            panic!("{}", e);
+        }
+        return;
+    }
+
     // Read input integer
     let mut input = String::new();
     print!("Input ID: ");
     if let Err(e) = io::stdout().flush() {
-        panic!(e);
+        panic!("{}", e);
     }
     if let Err(e) = io::stdin().read_line(&mut input) {
-        panic!(e);
+        panic!("{}", e);
     }
     let input = match input.trim().parse::<i64>() {
         Ok(i) => i,
         Err(e) => panic!("Error parsing input: {}", e),
     };
 
-    // Read file
-    let file = match std::fs::read_to_string("input") {
-        Ok(i) => i.trim().to_string(),
-        Err(e) => panic!("Error reading input {}", e),
-    };
-
-    let mut data = Vec::new();
-
-    for val in file.split(',') {
-        data.push(match val.parse::<i64>() {
-            Ok(v) => v,
-            Err(e) => panic!("Error parsing {}: {}", val, e),
-        });
-    }
-
-    let mut c = part1::Computer::new(data.clone());
+    let mut c = intcode::Computer::new(read_program());
+    // the ID is given once, a program asking for more stops awaiting input
     let mut output = Vec::new();
-    while !c.halted() {
-        let mut new_output = c.run(&[input]);
-        output.append(&mut new_output);
+    match c.run_io(&mut VecDeque::from(vec![input]), &mut output) {
+        Ok(intcode::RunState::Halted) => {}
+        Ok(_) => eprintln!("Program asked for more than one input"),
+        Err(e) => panic!("Error running program: {}", e),
     }
 
     println!("{:?}", output);
 }
+
+fn read_program() -> Vec<i64> {
+    match intcode::program::load("input") {
+        Ok(p) => p,
+        Err(e) => panic!("Error reading input {}", e),
+    }
+}
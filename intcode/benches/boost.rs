@@ -0,0 +1,52 @@
+// Times the day09 BOOST program in sensor boost mode (input 2), the longest running Intcode program so far,
+// and a synthetic countdown loop with a similar instruction mix
+// Puzzle inputs aren't checked in, so `BOOST_INPUT` must be set to the path of a BOOST program
+
+use intcode::Computer;
+use std::time::{Duration, Instant};
+
+const RUNS: u32 = 20;
+
+// Counts [100] down from 200000, mixing relative, position and immediate operands
+const COUNTDOWN: [i64; 27] = [
+    109, 200, // arb #200
+    21101, 0, 0, 0, // add #0, #0, rb+0
+    1001, 100, -1, 100, // add [100], #-1, [100]
+    22101, 3, 0, 1, // add #3, rb+0, rb+1
+    22102, -1, 1, 0, // mul #-1, rb+1, rb+0
+    1005, 100, 6, // jt [100], #6
+    204, 0,  // out rb+0
+    99, // hlt
+    0, 0, 0,
+];
+
+fn main() {
+    let path = match std::env::var("BOOST_INPUT") {
+        Ok(path) => path,
+        Err(_) => panic!("Set BOOST_INPUT to the path of a day09 BOOST program"),
+    };
+    let boost = match intcode::program::load(&path) {
+        Ok(data) => data,
+        Err(e) => panic!("Error reading {} {}", path, e),
+    };
+    let mut countdown = COUNTDOWN.to_vec();
+    countdown.resize(101, 0);
+    countdown[100] = 200_000;
+
+    time(&path, &boost, 2);
+    time("synthetic countdown", &countdown, 0);
+}
+
+fn time(name: &str, data: &[i64], input: i64) {
+    let mut best = Duration::from_secs(u64::MAX);
+    for _ in 0..RUNS {
+        let mut c = Computer::new(data.to_vec());
+        let start = Instant::now();
+        if let Err(e) = c.run(&[input]) {
+            panic!("Error running {}: {}", name, e);
+        }
+        best = best.min(start.elapsed());
+    }
+
+    println!("{}: best of {} runs {:?}", name, RUNS, best);
+}
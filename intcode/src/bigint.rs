@@ -0,0 +1,308 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Mul, Neg};
+use std::str::FromStr;
+
+// Largest power of 10 that fits in a limb, used to convert to and from decimal 9 digits at a time
+const DECIMAL_BASE: u32 = 1_000_000_000;
+
+// Arbitrary precision signed integer, for running programs whose values outgrow 64 bits
+// Stored as sign and magnitude with the magnitude in base 2^32 limbs, least significant first
+// and without trailing zero limbs, so zero is an empty magnitude and never negative
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BigInt {
+    negative: bool,
+    magnitude: Vec<u32>,
+}
+
+impl BigInt {
+    fn from_parts(negative: bool, mut magnitude: Vec<u32>) -> Self {
+        while magnitude.last() == Some(&0) {
+            magnitude.pop();
+        }
+        let negative = negative && !magnitude.is_empty();
+        Self {
+            negative,
+            magnitude,
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.magnitude.is_empty()
+    }
+
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.magnitude.len() > 2 {
+            return None;
+        }
+        let mut magnitude: u64 = 0;
+        for &limb in self.magnitude.iter().rev() {
+            magnitude = magnitude << 32 | u64::from(limb);
+        }
+        if self.negative {
+            if magnitude <= 1 << 63 {
+                Some((magnitude as i64).wrapping_neg())
+            } else {
+                None
+            }
+        } else if magnitude < 1 << 63 {
+            Some(magnitude as i64)
+        } else {
+            None
+        }
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(val: i64) -> Self {
+        let magnitude = val.unsigned_abs();
+        Self::from_parts(val < 0, vec![magnitude as u32, (magnitude >> 32) as u32])
+    }
+}
+
+fn cmp_magnitude(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut sum = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0;
+    for i in 0..a.len().max(b.len()) {
+        let total = u64::from(*a.get(i).unwrap_or(&0)) + u64::from(*b.get(i).unwrap_or(&0)) + carry;
+        sum.push(total as u32);
+        carry = total >> 32;
+    }
+    sum.push(carry as u32);
+    sum
+}
+
+// `a - b` where `a` is at least `b`
+fn sub_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut difference = Vec::with_capacity(a.len());
+    let mut borrow = 0;
+    for (i, &limb) in a.iter().enumerate() {
+        let total = i64::from(limb) - i64::from(*b.get(i).unwrap_or(&0)) - borrow;
+        if total < 0 {
+            difference.push((total + (1 << 32)) as u32);
+            borrow = 1;
+        } else {
+            difference.push(total as u32);
+            borrow = 0;
+        }
+    }
+    difference
+}
+
+// Divides `magnitude` in place by `divisor`, returning the remainder
+fn div_rem_small(magnitude: &mut Vec<u32>, divisor: u32) -> u32 {
+    let mut rem: u64 = 0;
+    for limb in magnitude.iter_mut().rev() {
+        let current = rem << 32 | u64::from(*limb);
+        *limb = (current / u64::from(divisor)) as u32;
+        rem = current % u64::from(divisor);
+    }
+    while magnitude.last() == Some(&0) {
+        magnitude.pop();
+    }
+    rem as u32
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp_magnitude(&self.magnitude, &other.magnitude),
+            (true, true) => cmp_magnitude(&other.magnitude, &self.magnitude),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::from_parts(
+                self.negative,
+                add_magnitude(&self.magnitude, &other.magnitude),
+            );
+        }
+        match cmp_magnitude(&self.magnitude, &other.magnitude) {
+            Ordering::Less => BigInt::from_parts(
+                other.negative,
+                sub_magnitude(&other.magnitude, &self.magnitude),
+            ),
+            _ => BigInt::from_parts(
+                self.negative,
+                sub_magnitude(&self.magnitude, &other.magnitude),
+            ),
+        }
+    }
+}
+
+impl Mul for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: &BigInt) -> BigInt {
+        let mut product = vec![0u32; self.magnitude.len() + other.magnitude.len()];
+        for (i, &a) in self.magnitude.iter().enumerate() {
+            let mut carry: u64 = 0;
+            for (j, &b) in other.magnitude.iter().enumerate() {
+                let total = u64::from(a) * u64::from(b) + u64::from(product[i + j]) + carry;
+                product[i + j] = total as u32;
+                carry = total >> 32;
+            }
+            product[i + other.magnitude.len()] = carry as u32;
+        }
+        BigInt::from_parts(self.negative != other.negative, product)
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::from_parts(!self.negative, self.magnitude.clone())
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        let mut magnitude = self.magnitude.clone();
+        let mut chunks = Vec::new();
+        while !magnitude.is_empty() {
+            chunks.push(div_rem_small(&mut magnitude, DECIMAL_BASE));
+        }
+        if self.negative {
+            write!(f, "-")?;
+        }
+        let mut chunks = chunks.iter().rev();
+        if let Some(first) = chunks.next() {
+            write!(f, "{}", first)?;
+        }
+        for chunk in chunks {
+            write!(f, "{:09}", chunk)?;
+        }
+        Ok(())
+    }
+}
+
+// Error from parsing a `BigInt` that isn't an optionally signed string of decimal digits
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseBigIntError;
+
+impl fmt::Display for ParseBigIntError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid digit found in string")
+    }
+}
+
+impl std::error::Error for ParseBigIntError {}
+
+impl FromStr for BigInt {
+    type Err = ParseBigIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(ParseBigIntError);
+        }
+
+        let mut magnitude: Vec<u32> = Vec::new();
+        // the first chunk takes whatever doesn't divide evenly so the rest are exactly 9 digits
+        let first = match digits.len() % 9 {
+            0 => 9,
+            n => n,
+        };
+        let mut start = 0;
+        let mut end = first;
+        while start < digits.len() {
+            let chunk = &digits[start..end];
+            let scale = 10u64.pow((end - start) as u32);
+            let mut carry = chunk.parse::<u64>().map_err(|_| ParseBigIntError)?;
+            for limb in &mut magnitude {
+                let total = u64::from(*limb) * scale + carry;
+                *limb = total as u32;
+                carry = total >> 32;
+            }
+            if carry > 0 {
+                magnitude.push(carry as u32);
+            }
+            start = end;
+            end += 9;
+        }
+        Ok(Self::from_parts(negative, magnitude))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(s: &str) -> BigInt {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn arithmetic() {
+        let values = [
+            0,
+            1,
+            -1,
+            7,
+            -12_345,
+            1 << 31,
+            (1 << 32) + 5,
+            -(1 << 40),
+            i64::from(i32::MAX),
+            i64::MAX,
+            i64::MIN,
+        ];
+        for &a in &values {
+            for &b in &values {
+                let (x, y) = (BigInt::from(a), BigInt::from(b));
+                let (a, b) = (i128::from(a), i128::from(b));
+                assert_eq!((a + b).to_string(), (&x + &y).to_string(), "{} + {}", a, b);
+                assert_eq!((a * b).to_string(), (&x * &y).to_string(), "{} * {}", a, b);
+                assert_eq!(a.cmp(&b), x.cmp(&y), "{} cmp {}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn conversions() {
+        for &val in &[0, 5, -5, i64::MAX, i64::MIN] {
+            assert_eq!(Some(val), BigInt::from(val).to_i64());
+            assert_eq!(BigInt::from(val), big(&val.to_string()));
+        }
+        assert_eq!(None, (&BigInt::from(i64::MAX) + &BigInt::from(1)).to_i64());
+        assert_eq!(None, (&BigInt::from(i64::MIN) + &BigInt::from(-1)).to_i64());
+
+        let huge = "-123456789012345678901234567890123456789";
+        assert_eq!(huge, big(huge).to_string());
+        assert_eq!(big("1000000000000000000"), big("+000001000000000000000000"));
+        assert_eq!(BigInt::default(), big("-0"));
+        assert_eq!(
+            big("1267650600228229401496703205376"), // 2^100
+            (0..100).fold(BigInt::from(1), |acc, _| &acc * &BigInt::from(2))
+        );
+        for bad in &["", "-", "12a", "1 2", "--1"] {
+            assert_eq!(Err(ParseBigIntError), bad.parse::<BigInt>());
+        }
+    }
+}
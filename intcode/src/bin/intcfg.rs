@@ -0,0 +1,29 @@
+// Prints the control-flow graph of a program without running it
+// usage: intcfg [file] [--dot]
+// `--dot` prints Graphviz DOT instead of the block summary, e.g. `intcfg input --dot | dot -Tsvg > cfg.svg`
+fn main() {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let dot = match args.iter().position(|a| a == "--dot") {
+        Some(i) => {
+            args.remove(i);
+            true
+        }
+        None => false,
+    };
+    let path = args
+        .into_iter()
+        .next()
+        .unwrap_or_else(|| "input".to_string());
+
+    let data = match intcode::program::load(&path) {
+        Ok(p) => p,
+        Err(e) => panic!("Error reading {} {}", path, e),
+    };
+
+    let graph = intcode::cfg::build(&data);
+    if dot {
+        print!("{}", graph.to_dot());
+    } else {
+        print!("{}", graph);
+    }
+}
@@ -0,0 +1,139 @@
+use intcode::{Computer, RunState};
+use std::collections::BTreeSet;
+use std::io::{self, prelude::*};
+
+const HELP: &str = "commands:
+  b <addr>          set breakpoint
+  d <addr>          delete breakpoint
+  s [n]             step n instructions (default 1)
+  c                 continue until breakpoint, input or halt
+  u [n]             undo n instructions (default 1)
+  uw <addr>         undo back to before the last write to addr
+  i <val>...        queue input values
+  m [start [len]]   print memory (default 0 16)
+  r                 print instruction pointer, relative base and halt state
+  t                 toggle instruction tracing
+  q                 quit";
+
+fn main() {
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "input".to_string());
+
+    let data = match intcode::program::load(&path) {
+        Ok(p) => p,
+        Err(e) => panic!("Error reading {} {}", path, e),
+    };
+
+    let mut c = Computer::new(data);
+    c.enable_journal(Some(JOURNAL_CAPACITY));
+    let mut breakpoints = BTreeSet::new();
+    let mut tracing = false;
+
+    println!("{}", HELP);
+    loop {
+        print!("({}) ", c.offset());
+        if let Err(e) = io::stdout().flush() {
+            panic!("{}", e);
+        }
+        let mut line = String::new();
+        match io::stdin().read_line(&mut line) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(e) => panic!("{}", e),
+        }
+        let mut words = line.split_whitespace();
+        let cmd = match words.next() {
+            Some(cmd) => cmd,
+            None => continue,
+        };
+        let args: Result<Vec<i64>, _> = words.map(str::parse::<i64>).collect();
+        let args = match args {
+            Ok(args) => args,
+            Err(e) => {
+                println!("Error parsing arguments: {}", e);
+                continue;
+            }
+        };
+
+        match (cmd, args.as_slice()) {
+            ("b", &[addr]) if addr >= 0 => {
+                breakpoints.insert(addr as usize);
+                println!("breakpoints: {:?}", breakpoints);
+            }
+            ("d", &[addr]) if addr >= 0 => {
+                breakpoints.remove(&(addr as usize));
+                println!("breakpoints: {:?}", breakpoints);
+            }
+            ("s", &[]) => step(&mut c, 1, &BTreeSet::new()),
+            ("s", &[n]) if n > 0 => step(&mut c, n as usize, &BTreeSet::new()),
+            ("c", &[]) => step(&mut c, usize::MAX, &breakpoints),
+            ("u", &[]) => println!("undid {} instructions", c.rewind(1)),
+            ("u", &[n]) if n > 0 => println!("undid {} instructions", c.rewind(n as usize)),
+            ("uw", &[addr]) if addr >= 0 => match c.rewind_to_write(addr as usize) {
+                Some(n) => println!("undid {} instructions", n),
+                None => println!("no recorded write to {}", addr),
+            },
+            ("i", vals) => c.push_input(vals),
+            ("m", &[]) => print_memory(&c, 0, 16),
+            ("m", &[start]) if start >= 0 => print_memory(&c, start as usize, 16),
+            ("m", &[start, len]) if start >= 0 && len >= 0 => {
+                print_memory(&c, start as usize, len as usize)
+            }
+            ("r", &[]) => println!(
+                "ip {} rb {} halted {}",
+                c.offset(),
+                c.relative_offset(),
+                c.halted()
+            ),
+            ("t", &[]) => {
+                tracing = !tracing;
+                if tracing {
+                    c.set_tracer(|trace| println!("{}", trace));
+                } else {
+                    c.clear_tracer();
+                }
+                println!("tracing {}", if tracing { "on" } else { "off" });
+            }
+            ("q", &[]) => break,
+            _ => println!("{}", HELP),
+        }
+    }
+}
+
+// Number of instructions the debugger can undo
+const JOURNAL_CAPACITY: usize = 1_000_000;
+
+// Executes up to `n` instructions, stopping early on halt, starved input or a breakpoint
+// The instruction at the current offset always runs so continuing from a breakpoint makes progress
+fn step(c: &mut Computer, n: usize, breakpoints: &BTreeSet<usize>) {
+    for i in 0..n {
+        if i > 0 && breakpoints.contains(&c.offset()) {
+            println!("breakpoint at {}", c.offset());
+            return;
+        }
+        match c.step() {
+            Ok(None) => {}
+            Ok(Some(RunState::Output(val))) => println!("output: {}", val),
+            Ok(Some(RunState::AwaitingInput)) => {
+                println!("awaiting input, queue some with `i`");
+                return;
+            }
+            Ok(Some(RunState::Halted)) => {
+                println!("halted");
+                return;
+            }
+            Err(e) => {
+                println!("error: {}", e);
+                return;
+            }
+        }
+    }
+}
+
+fn print_memory(c: &Computer, start: usize, len: usize) {
+    for (row, chunk) in c.read_memory(start, len).chunks(8).enumerate() {
+        let vals: Vec<String> = chunk.iter().map(i64::to_string).collect();
+        println!("{:>6}: {}", start + row * 8, vals.join(" "));
+    }
+}
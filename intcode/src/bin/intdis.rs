@@ -0,0 +1,12 @@
+fn main() {
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "input".to_string());
+
+    let data = match intcode::program::load(&path) {
+        Ok(p) => p,
+        Err(e) => panic!("Error reading {} {}", path, e),
+    };
+
+    print!("{}", intcode::disasm::disassemble(&data));
+}
@@ -0,0 +1,51 @@
+use intcode::fuzz::{self, Features, Interpreter};
+use std::collections::HashSet;
+
+// Instructions each interpreter may run per case, so generated infinite loops end
+const STEPS: u64 = 10_000;
+
+// Runs random programs through every interpreter and prints a minimized reproducer for each disagreement
+// Most runs find the same few differences over and over, so each reproducer is only printed once
+// usage: intfuzz [cases] [seed] [--legacy]
+// `--legacy` only generates programs the old interpreters supported, leaving day 5 reusing its input as the difference
+fn main() {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let features = match args.iter().position(|a| a == "--legacy") {
+        Some(i) => {
+            args.remove(i);
+            Features::LEGACY
+        }
+        None => Features::ALL,
+    };
+    let mut args = args.into_iter();
+    let cases = match args.next().map(|a| a.parse::<usize>()) {
+        Some(Ok(cases)) => cases,
+        Some(Err(e)) => panic!("Error parsing cases: {}", e),
+        None => 1000,
+    };
+    let seed = match args.next().map(|a| a.parse::<u64>()) {
+        Some(Ok(seed)) => seed,
+        Some(Err(e)) => panic!("Error parsing seed: {}", e),
+        None => 0,
+    };
+
+    let mut rng = fuzz::Rng::new(seed);
+    let mut found = 0;
+    let mut reported = HashSet::new();
+    for _ in 0..cases {
+        let case = fuzz::generate(&mut rng, features);
+        if let Some(divergence) = fuzz::Divergence::check(&case, &Interpreter::ALL, STEPS) {
+            found += 1;
+            let minimal = fuzz::minimize(&divergence, &Interpreter::ALL, STEPS);
+            if reported.insert(minimal.case.clone()) {
+                println!("{}", minimal);
+            }
+        }
+    }
+    println!(
+        "{} of {} cases diverged, {} distinct reproducers",
+        found,
+        cases,
+        reported.len()
+    );
+}
@@ -0,0 +1,32 @@
+// Converts a program between text and the compact binary format `intcode::program::load` also reads
+// usage: intpack <from> <to> [--text]
+// writes binary unless `--text` is given, which writes comma separated text
+fn main() {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let text = match args.iter().position(|a| a == "--text") {
+        Some(i) => {
+            args.remove(i);
+            true
+        }
+        None => false,
+    };
+    let (from, to) = match args.as_slice() {
+        [from, to] => (from, to),
+        _ => panic!("usage: intpack <from> <to> [--text]"),
+    };
+
+    let data = match intcode::program::load(from) {
+        Ok(p) => p,
+        Err(e) => panic!("Error reading {} {}", from, e),
+    };
+
+    let result = if text {
+        let vals: Vec<String> = data.iter().map(i64::to_string).collect();
+        std::fs::write(to, vals.join(",") + "\n")
+    } else {
+        intcode::program::save_binary(to, &data)
+    };
+    if let Err(e) = result {
+        panic!("Error writing {} {}", to, e);
+    }
+}
@@ -0,0 +1,47 @@
+// Runs a program with profiling enabled and prints the report
+// usage: intprof [file] [input]... [--folded]
+// `--folded` prints flamegraph folded stacks instead of the tables
+fn main() {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let folded = match args.iter().position(|a| a == "--folded") {
+        Some(i) => {
+            args.remove(i);
+            true
+        }
+        None => false,
+    };
+    let mut args = args.into_iter();
+    let path = args.next().unwrap_or_else(|| "input".to_string());
+
+    let mut input = Vec::new();
+    for val in args {
+        input.push(match val.parse::<i64>() {
+            Ok(v) => v,
+            Err(e) => panic!("Error parsing {}: {}", val, e),
+        });
+    }
+
+    let data = match intcode::program::load(&path) {
+        Ok(p) => p,
+        Err(e) => panic!("Error reading {} {}", path, e),
+    };
+
+    let mut c = intcode::Computer::new(data);
+    c.enable_profiling();
+    let result = c.run(&input);
+    let profile = c.take_profile().unwrap_or_default();
+
+    if folded {
+        print!("{}", profile.folded());
+        return;
+    }
+    match result {
+        Ok(output) => println!("output: {:?}", output),
+        Err(e) => println!("error: {}", e),
+    }
+    if !c.halted() {
+        println!("stopped awaiting input");
+    }
+    println!();
+    print!("{}", profile);
+}
@@ -0,0 +1,33 @@
+// Prints every cell a straight-line program writes in terms of symbols
+// usage: intsym [file] [address=name]...
+// defaults to `input` with mem[1] as noun and mem[2] as verb, like day 2
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let path = args.next().unwrap_or_else(|| "input".to_string());
+
+    let mut symbols = Vec::new();
+    for arg in args {
+        let (address, name) = match arg.split_once('=') {
+            Some((address, name)) => (address, name.to_string()),
+            None => panic!("Expected address=name, got {}", arg),
+        };
+        match address.parse::<usize>() {
+            Ok(address) => symbols.push((address, name)),
+            Err(e) => panic!("Error parsing {}: {}", address, e),
+        }
+    }
+    if symbols.is_empty() {
+        symbols = vec![(1, "noun".to_string()), (2, "verb".to_string())];
+    }
+
+    let data = match intcode::program::load(&path) {
+        Ok(p) => p,
+        Err(e) => panic!("Error reading {} {}", path, e),
+    };
+
+    let symbols: Vec<(usize, &str)> = symbols.iter().map(|(a, n)| (*a, n.as_str())).collect();
+    match intcode::symbolic::analyze(&intcode::Computer::new(data), &symbols) {
+        Ok(analysis) => print!("{}", analysis),
+        Err(e) => panic!("{}", e),
+    }
+}
@@ -0,0 +1,131 @@
+use crate::BigInt;
+use std::convert::TryFrom;
+use std::fmt::{Debug, Display};
+
+// What `add` and `mul` do when a result doesn't fit in the cell type, set with `Computer::set_overflow`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Overflow {
+    // Fail with `IntcodeError::Overflow`
+    #[default]
+    Error,
+    // Two's complement wrap around
+    Wrap,
+    // Clamp to the smallest or largest value
+    Saturate,
+}
+
+// Value held in each memory cell of a `Computer`, chosen through its `Memory`
+// Implemented for `i32`, `i64`, `i128` and `BigInt`, so e.g. `Computer<DenseMemory<i32>>` halves memory use
+// `Default` must be zero and every cell type must be able to hold 0 and 1
+pub trait Cell: Clone + Debug + Default + Display + Ord + Send + Sync + 'static {
+    fn from_i64(val: i64) -> Option<Self>;
+
+    fn to_i64(&self) -> Option<i64>;
+
+    // Returns `None` if the result doesn't fit, which can only happen with `Overflow::Error`
+    fn add(&self, other: &Self, overflow: Overflow) -> Option<Self>;
+
+    fn mul(&self, other: &Self, overflow: Overflow) -> Option<Self>;
+}
+
+macro_rules! primitive_cell {
+    ($($t:ty),*) => {
+        $(
+            impl Cell for $t {
+                fn from_i64(val: i64) -> Option<Self> {
+                    <$t>::try_from(val).ok()
+                }
+
+                fn to_i64(&self) -> Option<i64> {
+                    i64::try_from(*self).ok()
+                }
+
+                fn add(&self, other: &Self, overflow: Overflow) -> Option<Self> {
+                    match overflow {
+                        Overflow::Error => self.checked_add(*other),
+                        Overflow::Wrap => Some(self.wrapping_add(*other)),
+                        Overflow::Saturate => Some(self.saturating_add(*other)),
+                    }
+                }
+
+                fn mul(&self, other: &Self, overflow: Overflow) -> Option<Self> {
+                    match overflow {
+                        Overflow::Error => self.checked_mul(*other),
+                        Overflow::Wrap => Some(self.wrapping_mul(*other)),
+                        Overflow::Saturate => Some(self.saturating_mul(*other)),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+primitive_cell!(i32, i64, i128);
+
+// Never overflows, so the policy is ignored
+impl Cell for BigInt {
+    fn from_i64(val: i64) -> Option<Self> {
+        Some(Self::from(val))
+    }
+
+    fn to_i64(&self) -> Option<i64> {
+        BigInt::to_i64(self)
+    }
+
+    fn add(&self, other: &Self, _: Overflow) -> Option<Self> {
+        Some(self + other)
+    }
+
+    fn mul(&self, other: &Self, _: Overflow) -> Option<Self> {
+        Some(self * other)
+    }
+}
+
+// `val` as an `i64`, clamped if it doesn't fit
+pub(crate) fn clamp<T: Cell>(val: &T) -> i64 {
+    match val.to_i64() {
+        Some(v) => v,
+        None if *val < T::default() => i64::MIN,
+        None => i64::MAX,
+    }
+}
+
+// 1 if `b` is true, otherwise 0
+pub(crate) fn flag<T: Cell>(b: bool) -> T {
+    T::from_i64(i64::from(b)).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overflow() {
+        let max = i64::MAX;
+        assert_eq!(None, max.add(&1, Overflow::Error));
+        assert_eq!(Some(i64::MIN), max.add(&1, Overflow::Wrap));
+        assert_eq!(Some(max), max.add(&1, Overflow::Saturate));
+        assert_eq!(None, max.mul(&2, Overflow::Error));
+        assert_eq!(Some(-2), max.mul(&2, Overflow::Wrap));
+        assert_eq!(Some(i64::MIN), max.mul(&-2, Overflow::Saturate));
+        assert_eq!(Some(6), 2.mul(&3, Overflow::Error));
+
+        assert_eq!(None, i32::MAX.add(&1, Overflow::Error));
+        assert_eq!(Some(i32::MIN), i32::MIN.mul(&2, Overflow::Saturate));
+        assert_eq!(None, i32::from_i64(1 << 40));
+        assert_eq!(Some(-5), (-5i32).to_i64());
+        assert_eq!(None, (1i128 << 70).to_i64());
+        assert_eq!(Some(1 << 70), (1i128 << 69).mul(&2, Overflow::Error));
+    }
+
+    #[test]
+    fn big() {
+        let max = BigInt::from(i64::MAX);
+        let sum = max.add(&BigInt::from(1), Overflow::Error).unwrap();
+        assert_eq!("9223372036854775808", sum.to_string());
+        assert_eq!(None, Cell::to_i64(&sum));
+        assert_eq!(i64::MAX, clamp(&sum));
+        assert_eq!(i64::MIN, clamp(&-&sum));
+        assert_eq!(BigInt::from(1), flag(true));
+    }
+}
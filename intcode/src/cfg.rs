@@ -0,0 +1,327 @@
+use crate::disasm::{self, Flow, Instruction};
+use crate::OPCODES;
+use std::collections::BTreeSet;
+use std::fmt::{self, Write};
+use std::ops::Range;
+
+// Straight-line run of instructions that's only entered at its first instruction and only left after its last
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Block {
+    pub start: usize,
+    pub end: usize, // one past the last word of the last instruction
+    pub instructions: Vec<(usize, Instruction)>,
+    pub successors: Vec<usize>, // starts of the blocks control can pass to
+    pub dynamic: bool,          // ends in a jump whose target is only known at runtime
+    pub reachable: bool,        // false for dead code that only decodes as instructions
+}
+
+// Instruction that writes to an address holding a reachable instruction
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CodeWrite {
+    pub offset: usize,
+    pub address: usize,
+}
+
+// Control-flow graph of a program, recovered without running it
+// Only immediate jump targets are followed and only position mode writes are checked for self-modification,
+// so programs that compute jump targets or write through the relative base may have more code than is shown
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Graph {
+    pub blocks: Vec<Block>, // in address order
+    pub writes: Vec<CodeWrite>,
+    pub data: Vec<Range<usize>>, // words that are neither reachable nor decodable as dead code
+    labels: BTreeSet<usize>,
+}
+
+// Splits `data` into basic blocks, following every statically known path from address 0
+// Words no path reaches are swept linearly and anything that decodes is kept as an unreachable block
+pub fn build(data: &[i64]) -> Graph {
+    let (starts, labels) = disasm::analyze(data);
+    let mut blocks = Vec::new();
+    let mut code = vec![false; data.len()]; // words belonging to reachable instructions
+
+    for &offset in &starts {
+        let ins = disasm::decode(data, offset).unwrap();
+        let end = offset + 1 + ins.params.len();
+        for word in &mut code[offset..end] {
+            *word = true;
+        }
+        push_instruction(&mut blocks, &labels, data.len(), offset, ins, true);
+    }
+
+    let mut unreached = Vec::new();
+    let mut offset = 0;
+    while offset < data.len() {
+        let ins = disasm::decode(data, offset).filter(|ins| {
+            let end = offset + 1 + ins.params.len();
+            !code[offset..end].iter().any(|&c| c)
+        });
+        match ins {
+            Some(ins) => {
+                let len = 1 + ins.params.len();
+                push_instruction(&mut unreached, &labels, data.len(), offset, ins, false);
+                offset += len;
+            }
+            None => {
+                if !code[offset] {
+                    unreached.push(Block {
+                        start: offset,
+                        end: offset + 1,
+                        instructions: Vec::new(),
+                        successors: Vec::new(),
+                        dynamic: false,
+                        reachable: false,
+                    });
+                }
+                offset += 1;
+            }
+        }
+    }
+
+    // words that didn't decode were pushed as empty blocks so neighbouring instructions aren't merged across them
+    let mut words: Vec<Range<usize>> = Vec::new();
+    for block in unreached {
+        if !block.instructions.is_empty() {
+            blocks.push(block);
+            continue;
+        }
+        match words.last_mut() {
+            Some(range) if range.end == block.start => range.end = block.end,
+            _ => words.push(block.start..block.end),
+        }
+    }
+    blocks.sort_by_key(|b| b.start);
+
+    let block_starts: BTreeSet<usize> = blocks.iter().map(|b| b.start).collect();
+    for block in &mut blocks {
+        let (_, ins) = block.instructions.last().unwrap();
+        let flow = Flow::of(ins, data.len());
+        block.dynamic = flow.dynamic;
+        if let Some(target) = flow.target {
+            block.successors.push(target);
+        }
+        if flow.falls_through {
+            block.successors.push(block.end);
+        }
+        block.successors.retain(|s| block_starts.contains(s));
+        block.successors.dedup();
+    }
+
+    let mut writes = Vec::new();
+    for &offset in &starts {
+        let ins = disasm::decode(data, offset).unwrap();
+        let writes_last = OPCODES.iter().any(|op| op.0 == ins.opcode && op.3);
+        let last = ins.params.len().wrapping_sub(1);
+        if writes_last && ins.modes[last] == 0 {
+            let address = ins.params[last];
+            if address >= 0 && code.get(address as usize) == Some(&true) {
+                writes.push(CodeWrite {
+                    offset,
+                    address: address as usize,
+                });
+            }
+        }
+    }
+
+    Graph {
+        blocks,
+        writes,
+        data: words,
+        labels,
+    }
+}
+
+// Adds the instruction at `offset` to the last block, or starts a new block if it can't continue it
+fn push_instruction(
+    blocks: &mut Vec<Block>,
+    labels: &BTreeSet<usize>,
+    len: usize,
+    offset: usize,
+    ins: Instruction,
+    reachable: bool,
+) {
+    let continues = match blocks.last() {
+        Some(block) => match block.instructions.last() {
+            Some((_, last)) => {
+                let flow = Flow::of(last, len);
+                block.end == offset
+                    && flow.falls_through
+                    && flow.target.is_none()
+                    && !flow.dynamic
+                    && !labels.contains(&offset)
+            }
+            None => false,
+        },
+        None => false,
+    };
+    let end = offset + 1 + ins.params.len();
+    if continues {
+        let block = blocks.last_mut().unwrap();
+        block.instructions.push((offset, ins));
+        block.end = end;
+    } else {
+        blocks.push(Block {
+            start: offset,
+            end,
+            instructions: vec![(offset, ins)],
+            successors: Vec::new(),
+            dynamic: false,
+            reachable,
+        });
+    }
+}
+
+impl Graph {
+    // Block containing the instruction at `offset`
+    pub fn block(&self, offset: usize) -> Option<&Block> {
+        self.blocks
+            .iter()
+            .find(|b| b.instructions.iter().any(|(o, _)| *o == offset))
+    }
+
+    pub fn reachable(&self) -> impl Iterator<Item = &Block> {
+        self.blocks.iter().filter(|b| b.reachable)
+    }
+
+    pub fn dead(&self) -> impl Iterator<Item = &Block> {
+        self.blocks.iter().filter(|b| !b.reachable)
+    }
+
+    // Graphviz DOT of the graph, with dead blocks dashed, self-modifying writes as red dashed edges,
+    // and jumps with runtime targets as dotted edges to a `?` node
+    pub fn to_dot(&self) -> String {
+        let mut out = String::new();
+        writeln!(out, "digraph intcode {{").unwrap();
+        writeln!(out, "    node [shape=box, fontname=\"monospace\"];").unwrap();
+
+        for block in &self.blocks {
+            let mut label = String::new();
+            for (offset, ins) in &block.instructions {
+                let text = disasm::format_instruction(ins, &self.labels);
+                write!(label, "{}: {}\\l", offset, escape(&text)).unwrap();
+            }
+            let style = if block.reachable {
+                ""
+            } else {
+                ", style=dashed, fontcolor=gray"
+            };
+            writeln!(out, "    b{} [label=\"{}\"{}];", block.start, label, style).unwrap();
+        }
+
+        for block in &self.blocks {
+            for successor in &block.successors {
+                writeln!(out, "    b{} -> b{};", block.start, successor).unwrap();
+            }
+            if block.dynamic {
+                writeln!(out, "    b{} -> dynamic [style=dotted];", block.start).unwrap();
+            }
+        }
+        if self.blocks.iter().any(|b| b.dynamic) {
+            writeln!(out, "    dynamic [shape=diamond, label=\"?\"];").unwrap();
+        }
+
+        for write in &self.writes {
+            if let (Some(from), Some(to)) =
+                (self.block(write.offset), self.containing(write.address))
+            {
+                writeln!(
+                    out,
+                    "    b{} -> b{} [style=dashed, color=red, label=\"writes {}\"];",
+                    from.start, to.start, write.address
+                )
+                .unwrap();
+            }
+        }
+
+        writeln!(out, "}}").unwrap();
+        out
+    }
+
+    // Reachable block with an instruction covering `address`
+    fn containing(&self, address: usize) -> Option<&Block> {
+        self.reachable().find(|b| {
+            b.instructions
+                .iter()
+                .any(|(o, ins)| (*o..o + 1 + ins.params.len()).contains(&address))
+        })
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+// One line per block, write into code, and run of data words
+impl fmt::Display for Graph {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for block in &self.blocks {
+            let kind = if block.reachable { "block" } else { "dead" };
+            write!(f, "{} {}..{}", kind, block.start, block.end)?;
+            if !block.successors.is_empty() {
+                let successors: Vec<String> =
+                    block.successors.iter().map(usize::to_string).collect();
+                write!(f, " -> {}", successors.join(", "))?;
+            }
+            if block.dynamic {
+                write!(f, " -> ?")?;
+            }
+            writeln!(f)?;
+        }
+        for write in &self.writes {
+            writeln!(
+                f,
+                "write at {} into code at {}",
+                write.offset, write.address
+            )?;
+        }
+        for range in &self.data {
+            writeln!(f, "data {}..{}", range.start, range.end)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn graph() {
+        let data = vec![
+            1001, 100, 1, 100, // add [100], #1, [100]
+            1008, 100, 5, 101, // eq [100], #5, [101]
+            1006, 101, 0, // jf [101], #L0
+            1101, 0, 99, 17, // add #0, #99, [17] overwrites the target of the next jump
+            1105, 1, 18, // jt #1, #L18
+            6, 102, 103, // jf [102], [103]
+            99,  // hlt
+            104, 7, 99, // never reached
+            -1,
+        ];
+
+        let graph = build(&data);
+        assert_eq!(
+            "block 0..11 -> 0, 11
+block 11..18 -> 18
+block 18..21 -> 21 -> ?
+block 21..22
+dead 22..25
+write at 11 into code at 17
+data 25..26
+",
+            graph.to_string()
+        );
+        assert_eq!(Some(11), graph.block(15).map(|b| b.start));
+        assert_eq!(None, graph.block(16));
+        assert_eq!(vec![22], graph.dead().map(|b| b.start).collect::<Vec<_>>());
+
+        let dot = graph.to_dot();
+        assert!(dot.contains("    b0 [label=\"0: add [100], #1, [100]\\l4: eq [100], #5, [101]\\l8: jf [101], #L0\\l\"];\n"));
+        assert!(dot.contains("    b0 -> b0;\n    b0 -> b11;\n"));
+        assert!(dot.contains("    b18 -> dynamic [style=dotted];\n"));
+        assert!(dot.contains("    b11 -> b11 [style=dashed, color=red, label=\"writes 17\"];\n"));
+        assert!(dot.contains(
+            "    b22 [label=\"22: out #7\\l24: hlt\\l\", style=dashed, fontcolor=gray];\n"
+        ));
+    }
+}
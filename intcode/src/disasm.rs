@@ -0,0 +1,279 @@
+#![allow(clippy::cast_possible_wrap)]
+
+use crate::OPCODES;
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+const DATA_WORDS_PER_LINE: usize = 8;
+
+// A single instruction decoded from memory
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Instruction {
+    pub opcode: i64,
+    pub mnemonic: &'static str,
+    pub modes: [i64; 3],
+    pub params: Vec<i64>, // raw parameter words, before applying modes
+}
+
+// Decodes the instruction at `offset`
+// Returns `None` if the word there isn't a valid instruction, uses an unknown or immediate write mode,
+// or its parameters run past the end of `data`
+// Like `Computer`, the mode digits of params the opcode doesn't have are ignored
+pub fn decode(data: &[i64], offset: usize) -> Option<Instruction> {
+    let (opcode, modes) = crate::decode(*data.get(offset)?)?;
+    let &(_, mnemonic, count, writes) = OPCODES.iter().find(|op| op.0 == opcode)?;
+
+    for (i, &mode) in modes.iter().enumerate() {
+        let valid = if i >= count {
+            true
+        } else if writes && i == count - 1 {
+            mode == 0 || mode == 2
+        } else {
+            mode <= 2
+        };
+        if !valid {
+            return None;
+        }
+    }
+
+    let params = data.get(offset + 1..offset + 1 + count)?.to_vec();
+    Some(Instruction {
+        opcode,
+        mnemonic,
+        modes,
+        params,
+    })
+}
+
+// Follows every statically known path from address 0, returning the offsets of all reachable instructions
+// and the targets of all immediate jumps
+pub fn analyze(data: &[i64]) -> (BTreeSet<usize>, BTreeSet<usize>) {
+    let mut starts = BTreeSet::new();
+    let mut labels = BTreeSet::new();
+    let mut pending = vec![0];
+
+    while let Some(offset) = pending.pop() {
+        if starts.contains(&offset) {
+            continue;
+        }
+        let ins = match decode(data, offset) {
+            Some(ins) => ins,
+            None => continue,
+        };
+        starts.insert(offset);
+
+        let flow = Flow::of(&ins, data.len());
+        if let Some(target) = flow.target {
+            labels.insert(target);
+            pending.push(target);
+        }
+        if flow.falls_through {
+            pending.push(offset + 1 + ins.params.len());
+        }
+    }
+
+    (starts, labels)
+}
+
+// Where execution can go after an instruction, as far as is known without running it
+pub(crate) struct Flow {
+    pub target: Option<usize>, // immediate jump target inside memory, if the jump can be taken
+    pub dynamic: bool,         // the jump can be taken to a target only known at runtime
+    pub falls_through: bool,   // execution can continue with the next instruction
+}
+
+impl Flow {
+    pub fn of(ins: &Instruction, len: usize) -> Self {
+        match ins.opcode {
+            5 | 6 => {
+                // a jump whose condition is immediate either always or never jumps
+                let (always, never) = if ins.modes[0] == 1 {
+                    let taken = (ins.params[0] != 0) == (ins.opcode == 5);
+                    (taken, !taken)
+                } else {
+                    (false, false)
+                };
+                let immediate = ins.modes[1] == 1;
+                let target = if !never && immediate && (0..len as i64).contains(&ins.params[1]) {
+                    Some(ins.params[1] as usize)
+                } else {
+                    None
+                };
+                Self {
+                    target,
+                    dynamic: !never && !immediate,
+                    falls_through: !always,
+                }
+            }
+            99 => Self {
+                target: None,
+                dynamic: false,
+                falls_through: false,
+            },
+            _ => Self {
+                target: None,
+                dynamic: false,
+                falls_through: true,
+            },
+        }
+    }
+}
+
+// Produces a listing of `data` with one instruction per line, labels on the targets of immediate jumps,
+// and `.data` lines for words that static analysis never reaches
+pub fn disassemble(data: &[i64]) -> String {
+    let (starts, labels) = analyze(data);
+    let mut out = String::new();
+    let mut words = Vec::new(); // pending `.data` words
+    let mut words_start = 0;
+
+    let mut offset = 0;
+    while offset < data.len() {
+        if labels.contains(&offset) {
+            flush_data(&mut out, words_start, &mut words);
+            writeln!(out, "L{}:", offset).unwrap();
+        }
+
+        // an instruction overlapping another reachable instruction or label is shown as data
+        let ins = if starts.contains(&offset) {
+            decode(data, offset).filter(|ins| {
+                let end = offset + 1 + ins.params.len();
+                starts.range(offset + 1..end).next().is_none()
+                    && labels.range(offset + 1..end).next().is_none()
+            })
+        } else {
+            None
+        };
+
+        if let Some(ins) = ins {
+            flush_data(&mut out, words_start, &mut words);
+            writeln!(out, "{:>6}: {}", offset, format_instruction(&ins, &labels)).unwrap();
+            offset += 1 + ins.params.len();
+        } else {
+            if words.is_empty() {
+                words_start = offset;
+            }
+            words.push(data[offset]);
+            if words.len() == DATA_WORDS_PER_LINE {
+                flush_data(&mut out, words_start, &mut words);
+            }
+            offset += 1;
+        }
+    }
+    flush_data(&mut out, words_start, &mut words);
+
+    out
+}
+
+// Formats `ins` as `mnemonic operand, operand, ...`
+// Immediate jump targets that have a label are shown as `#L<target>`
+pub fn format_instruction(ins: &Instruction, labels: &BTreeSet<usize>) -> String {
+    let mut operands = Vec::new();
+    for (i, (&param, &mode)) in ins.params.iter().zip(ins.modes.iter()).enumerate() {
+        let is_target = (ins.opcode == 5 || ins.opcode == 6) && i == 1;
+        operands.push(match mode {
+            0 => format!("[{}]", param),
+            1 if is_target && param >= 0 && labels.contains(&(param as usize)) => {
+                format!("#L{}", param)
+            }
+            1 => format!("#{}", param),
+            _ if param < 0 => format!("rb{}", param),
+            _ => format!("rb+{}", param),
+        });
+    }
+
+    let text = if operands.is_empty() {
+        ins.mnemonic.to_string()
+    } else {
+        format!("{} {}", ins.mnemonic, operands.join(", "))
+    };
+    // the assembler can't reproduce mode digits on params that don't exist, so note the original word
+    if ins.modes[ins.params.len()..].iter().any(|&mode| mode != 0) {
+        let word = ins.opcode + ins.modes[0] * 100 + ins.modes[1] * 1000 + ins.modes[2] * 10_000;
+        format!("{} ; encoded as {}", text, word)
+    } else {
+        text
+    }
+}
+
+fn flush_data(out: &mut String, start: usize, words: &mut Vec<i64>) {
+    if words.is_empty() {
+        return;
+    }
+    let vals: Vec<String> = words.iter().map(i64::to_string).collect();
+    writeln!(out, "{:>6}: .data {}", start, vals.join(", ")).unwrap();
+    words.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn listing() {
+        let data = vec![3, 9, 8, 9, 10, 9, 4, 9, 99, -1, 8];
+
+        assert_eq!(
+            "     0: in [9]
+     2: eq [9], [10], [9]
+     6: out [9]
+     8: hlt
+     9: .data -1, 8
+",
+            disassemble(&data)
+        );
+    }
+
+    #[test]
+    fn labels() {
+        let data = vec![
+            109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+        ];
+
+        assert_eq!(
+            "L0:
+     0: arb #1
+     2: out rb-1
+     4: add [100], #1, [100]
+     8: eq [100], #16, [101]
+    12: jf [101], #L0
+    15: hlt
+",
+            disassemble(&data)
+        );
+    }
+
+    #[test]
+    fn unconditional_jump() {
+        // jumps over the data word at 3
+        let data = vec![1105, 1, 4, 7, 104, 5, 99];
+
+        let (starts, labels) = analyze(&data);
+        assert_eq!(vec![0, 4, 6], starts.into_iter().collect::<Vec<_>>());
+        assert_eq!(vec![4], labels.into_iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn unused_modes() {
+        // `Computer` ignores the modes of params an opcode doesn't have, so these still run
+        let data = vec![1104, 7, 91099];
+        let mut c = crate::Computer::new(data.clone());
+        assert_eq!(Ok(vec![7]), c.run(&[]));
+
+        assert_eq!(
+            "     0: out #7 ; encoded as 1104
+     2: hlt ; encoded as 91099
+",
+            disassemble(&data)
+        );
+        assert_eq!("block 0..3\n", crate::cfg::build(&data).to_string());
+    }
+
+    #[test]
+    fn invalid_instructions() {
+        assert_eq!(None, decode(&[11101, 1, 1, 0], 0)); // immediate write
+        assert_eq!(None, decode(&[304, 0], 0)); // unknown mode
+        assert_eq!(None, decode(&[1, 0, 0], 0)); // truncated
+        assert_eq!(None, decode(&[42], 0));
+    }
+}
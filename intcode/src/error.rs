@@ -0,0 +1,61 @@
+use crate::Limit;
+use std::fmt;
+
+// Errors raised while executing a program
+// Every variant carries `offset`, the instruction pointer of the instruction that failed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IntcodeError {
+    UnknownOpcode { opcode: i64, offset: usize },
+    InvalidMode { mode: i64, offset: usize },
+    ImmediateWrite { offset: usize },
+    NegativeAddress { address: i64, offset: usize },
+    MemoryLimit { address: usize, offset: usize },
+    // An `add` or `mul` overflowed under `Overflow::Error`, or an address or relative base went out of range
+    Overflow { offset: usize },
+    // Raised before executing the instruction at `offset`, so the computer can be resumed once the limit is lifted
+    LimitExceeded { limit: Limit, offset: usize },
+}
+
+impl IntcodeError {
+    pub fn offset(&self) -> usize {
+        match *self {
+            Self::UnknownOpcode { offset, .. }
+            | Self::InvalidMode { offset, .. }
+            | Self::ImmediateWrite { offset }
+            | Self::NegativeAddress { offset, .. }
+            | Self::MemoryLimit { offset, .. }
+            | Self::Overflow { offset }
+            | Self::LimitExceeded { offset, .. } => offset,
+        }
+    }
+}
+
+impl fmt::Display for IntcodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::UnknownOpcode { opcode, offset } => {
+                write!(f, "Unexpected opcode {} at {}", opcode, offset)
+            }
+            Self::InvalidMode { mode, offset } => {
+                write!(f, "Unrecognized mode {} at {}", mode, offset)
+            }
+            Self::ImmediateWrite { offset } => {
+                write!(f, "Unsupported immediate mode write at {}", offset)
+            }
+            Self::NegativeAddress { address, offset } => {
+                write!(f, "Negative address {} at {}", address, offset)
+            }
+            Self::MemoryLimit { address, offset } => {
+                write!(f, "Write to {} exceeds memory limit at {}", address, offset)
+            }
+            Self::Overflow { offset } => write!(f, "Overflow at {}", offset),
+            Self::LimitExceeded { limit, offset } => match limit {
+                Limit::Instructions => write!(f, "Instruction limit reached at {}", offset),
+                Limit::Timeout => write!(f, "Timed out at {}", offset),
+                Limit::Cancelled => write!(f, "Cancelled at {}", offset),
+            },
+        }
+    }
+}
+
+impl std::error::Error for IntcodeError {}
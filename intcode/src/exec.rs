@@ -0,0 +1,253 @@
+use crate::Output;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+
+struct Shared<T> {
+    queue: VecDeque<T>,
+    waker: Option<Waker>, // receiver waiting for a value, woken by the next send or the last sender dropping
+    senders: usize,
+}
+
+// Sending half of a `channel`, cloned to give a receiver several senders
+pub struct Sender<T> {
+    shared: Rc<RefCell<Shared<T>>>,
+}
+
+// Receiving half of a `channel`
+pub struct Receiver<T> {
+    shared: Rc<RefCell<Shared<T>>>,
+}
+
+// Unbounded single-threaded channel whose receiver can be awaited from a task on an `Executor`
+pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
+    let shared = Rc::new(RefCell::new(Shared {
+        queue: VecDeque::new(),
+        waker: None,
+        senders: 1,
+    }));
+    (
+        Sender {
+            shared: Rc::clone(&shared),
+        },
+        Receiver { shared },
+    )
+}
+
+impl<T> Sender<T> {
+    // Never blocks, values sent after the receiver is dropped are discarded
+    pub fn send(&self, val: T) {
+        let mut shared = self.shared.borrow_mut();
+        if Rc::strong_count(&self.shared) > shared.senders {
+            shared.queue.push_back(val);
+        }
+        if let Some(waker) = shared.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.shared.borrow_mut().senders += 1;
+        Self {
+            shared: Rc::clone(&self.shared),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut shared = self.shared.borrow_mut();
+        shared.senders -= 1;
+        if shared.senders == 0 {
+            if let Some(waker) = shared.waker.take() {
+                waker.wake();
+            }
+        }
+    }
+}
+
+impl<T> Output<T> for Sender<T> {
+    fn write(&mut self, val: T) {
+        self.send(val);
+    }
+}
+
+impl<T> Receiver<T> {
+    // Waits for the next value, resolving to `None` once the queue is empty and every sender is dropped
+    pub fn recv(&mut self) -> Recv<'_, T> {
+        Recv { receiver: self }
+    }
+
+    // Next value if one has already been sent
+    pub fn try_recv(&mut self) -> Option<T> {
+        self.shared.borrow_mut().queue.pop_front()
+    }
+}
+
+// Future returned by `Receiver::recv`
+pub struct Recv<'a, T> {
+    receiver: &'a mut Receiver<T>,
+}
+
+impl<T> Future for Recv<'_, T> {
+    type Output = Option<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<T>> {
+        let mut shared = self.receiver.shared.borrow_mut();
+        match shared.queue.pop_front() {
+            Some(val) => Poll::Ready(Some(val)),
+            None if shared.senders == 0 => Poll::Ready(None),
+            None => {
+                shared.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+// Wakes a task by queueing its id to be polled, unless it's already queued
+struct TaskWaker {
+    id: usize,
+    queued: AtomicBool,
+    ready: Arc<Mutex<VecDeque<usize>>>,
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        if !self.queued.swap(true, Ordering::SeqCst) {
+            self.ready.lock().unwrap().push_back(self.id);
+        }
+    }
+}
+
+struct Task {
+    future: Option<Pin<Box<dyn Future<Output = ()>>>>, // `None` once complete
+    waker: Arc<TaskWaker>,
+}
+
+// Handle to the result of a future spawned on an `Executor`
+pub struct JoinHandle<T> {
+    result: Rc<RefCell<Option<T>>>,
+}
+
+impl<T> JoinHandle<T> {
+    pub fn is_finished(&self) -> bool {
+        self.result.borrow().is_some()
+    }
+
+    // Result of the future if it has completed, which can only be taken once
+    pub fn take(&self) -> Option<T> {
+        self.result.borrow_mut().take()
+    }
+}
+
+// Single-threaded executor that only polls a task after it's woken, so a machine awaiting input costs nothing until a value is sent to it
+// Scheduling is cooperative, a task only gives up the thread when it awaits something that isn't ready
+pub struct Executor {
+    tasks: Vec<Task>,
+    ready: Arc<Mutex<VecDeque<usize>>>, // ids of tasks to poll, in the order they were woken
+}
+
+impl Executor {
+    pub fn new() -> Self {
+        Self {
+            tasks: Vec::new(),
+            ready: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+
+    // Adds a future to be polled by the next call to `run`
+    pub fn spawn<F: Future + 'static>(&mut self, future: F) -> JoinHandle<F::Output> {
+        let result = Rc::new(RefCell::new(None));
+        let slot = Rc::clone(&result);
+        let waker = Arc::new(TaskWaker {
+            id: self.tasks.len(),
+            queued: AtomicBool::new(false),
+            ready: Arc::clone(&self.ready),
+        });
+        waker.wake_by_ref();
+        self.tasks.push(Task {
+            future: Some(Box::pin(async move {
+                *slot.borrow_mut() = Some(future.await);
+            })),
+            waker,
+        });
+        JoinHandle { result }
+    }
+
+    // Polls woken tasks until none are left to run, returning how many are still blocked without completing
+    pub fn run(&mut self) -> usize {
+        loop {
+            let id = match self.ready.lock().unwrap().pop_front() {
+                Some(id) => id,
+                None => break,
+            };
+            let task = &mut self.tasks[id];
+            task.waker.queued.store(false, Ordering::SeqCst);
+            if let Some(future) = task.future.as_mut() {
+                let waker = Waker::from(Arc::clone(&task.waker));
+                if future
+                    .as_mut()
+                    .poll(&mut Context::from_waker(&waker))
+                    .is_ready()
+                {
+                    task.future = None;
+                }
+            }
+        }
+        self.tasks.iter().filter(|t| t.future.is_some()).count()
+    }
+}
+
+impl Default for Executor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn channel() {
+        let mut exec = Executor::new();
+        let (tx, mut rx) = super::channel();
+        let sum = exec.spawn(async move {
+            let mut sum = 0;
+            while let Some(val) = rx.recv().await {
+                sum += val;
+            }
+            sum
+        });
+
+        assert_eq!(1, exec.run());
+        tx.send(2);
+        tx.clone().send(3);
+        assert_eq!(1, exec.run());
+        assert!(!sum.is_finished());
+        drop(tx);
+        assert_eq!(0, exec.run());
+        assert_eq!(Some(5), sum.take());
+        assert_eq!(None, sum.take());
+    }
+
+    #[test]
+    fn dropped_receiver() {
+        let (mut tx, rx) = super::channel();
+        drop(rx);
+        tx.write(1);
+        assert_eq!(0, tx.shared.borrow().queue.len());
+    }
+}
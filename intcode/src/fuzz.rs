@@ -0,0 +1,616 @@
+use crate::{Computer, IntcodeError, Limit};
+use std::collections::VecDeque;
+use std::fmt;
+use std::ops::Range;
+
+// Deterministic xorshift64* generator, so a seed always reproduces the same cases
+#[derive(Clone, Debug)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // splitmix64 so nearby seeds start far apart, and the state must never be zero
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        Self((z ^ (z >> 31)).max(1))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    // Uniform value in `0..n`, `n` must not be 0
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    pub fn range(&mut self, range: Range<i64>) -> i64 {
+        range.start + (self.next_u64() % (range.end - range.start) as u64) as i64
+    }
+
+    // True `percent` times out of 100
+    pub fn chance(&mut self, percent: usize) -> bool {
+        self.below(100) < percent
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+}
+
+// What generated programs may do beyond what every interpreter supports
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Features {
+    pub relative: bool,      // `arb` and relative mode parameters
+    pub dynamic_jumps: bool, // jump targets read from memory rather than immediate
+    pub code_writes: bool,   // writes anywhere in the program rather than only its data words
+}
+
+impl Features {
+    // Only what day 5's and day 7's interpreters supported, so day 7 and `Computer` should always agree
+    pub const LEGACY: Self = Self {
+        relative: false,
+        dynamic_jumps: false,
+        code_writes: false,
+    };
+
+    pub const ALL: Self = Self {
+        relative: true,
+        dynamic_jumps: true,
+        code_writes: true,
+    };
+}
+
+// A program and the input it's run with
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Case {
+    pub program: Vec<i64>,
+    pub input: Vec<i64>,
+}
+
+// Words after the code that generated programs read and write
+const DATA_WORDS: usize = 8;
+const MAX_INSTRUCTIONS: usize = 12;
+const MAX_INPUTS: usize = 4;
+// Keeps the shared computer from allocating huge memories for computed addresses, the old interpreters never grew memory at all
+const MEMORY_LIMIT: usize = 1 << 16;
+// Small values so loops take a while to overflow
+const VALUES: Range<i64> = -20..21;
+
+// Random program of valid instructions ending in `hlt`, followed by a few data words
+pub fn generate(rng: &mut Rng, features: Features) -> Case {
+    let mut opcodes: Vec<i64> = (1..=8).collect();
+    if features.relative {
+        opcodes.push(9);
+    }
+    let mut code: Vec<i64> = (0..1 + rng.below(MAX_INSTRUCTIONS))
+        .map(|_| *rng.pick(&opcodes))
+        .collect();
+    code.push(99);
+
+    let mut starts = Vec::new();
+    let mut len = 0;
+    for &opcode in &code {
+        starts.push(len as i64);
+        len += 1 + crate::OPCODES.iter().find(|op| op.0 == opcode).unwrap().2;
+    }
+    let data: Range<i64> = len as i64..(len + DATA_WORDS) as i64;
+    let everywhere = 0..data.end;
+
+    let mut program = Vec::new();
+    for &opcode in &code {
+        let &(_, _, count, writes) = crate::OPCODES.iter().find(|op| op.0 == opcode).unwrap();
+        let mut modes = [0; 3];
+        let mut params = Vec::new();
+        for (i, mode) in modes.iter_mut().enumerate().take(count) {
+            let relative = features.relative && rng.chance(20);
+            let param = if writes && i == count - 1 {
+                if relative {
+                    *mode = 2;
+                    rng.range(-4..5)
+                } else if features.code_writes && rng.chance(20) {
+                    rng.range(everywhere.clone())
+                } else {
+                    rng.range(data.clone())
+                }
+            } else if (opcode == 5 || opcode == 6) && i == 1 {
+                if features.dynamic_jumps && rng.chance(20) {
+                    rng.range(data.clone())
+                } else {
+                    *mode = 1;
+                    *rng.pick(&starts)
+                }
+            } else if relative {
+                *mode = 2;
+                rng.range(-4..5)
+            } else if rng.chance(50) {
+                *mode = 1;
+                rng.range(VALUES)
+            } else {
+                rng.range(everywhere.clone())
+            };
+            params.push(param);
+        }
+        program.push(opcode + modes[0] * 100 + modes[1] * 1000 + modes[2] * 10_000);
+        program.extend(params);
+    }
+    program.extend((0..DATA_WORDS).map(|_| rng.range(VALUES)));
+
+    let input = (0..rng.below(MAX_INPUTS + 1))
+        .map(|_| rng.range(VALUES))
+        .collect();
+    Case { program, input }
+}
+
+// Why an interpreter stopped
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Stop {
+    Halted,
+    AwaitingInput,
+    StepLimit,
+    Crashed(String), // the interpreters fail in different ways, so only the fact that they crashed is compared
+}
+
+impl Stop {
+    fn same(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Crashed(_), Self::Crashed(_)) => true,
+            _ => self == other,
+        }
+    }
+}
+
+// Everything a run is compared on
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Outcome {
+    pub output: Vec<i64>,
+    pub memory: Vec<i64>, // the program's words when it stopped, ignoring anything written past its end
+    pub stop: Stop,
+}
+
+// The interpreters the days used before they shared `Computer`
+// Day 5 and day 7 are reference models of their old interpreters: memory is fixed to the program's length,
+// there's no relative base, any mode but 0 reads immediately and writes ignore their mode.
+// Day 5 stored its first input value at every `in`, ignoring the rest, where day 7 takes each in turn and
+// waits when input runs out
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Interpreter {
+    Day05,
+    Day07,
+    Day09, // the shared `Computer`, which started out as day 9's interpreter
+}
+
+impl Interpreter {
+    pub const ALL: [Self; 3] = [Self::Day05, Self::Day07, Self::Day09];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Day05 => "day05",
+            Self::Day07 => "day07",
+            Self::Day09 => "day09",
+        }
+    }
+
+    // Runs `case` for at most `steps` instructions
+    pub fn run(self, case: &Case, steps: u64) -> Outcome {
+        match self {
+            Self::Day05 => legacy(case, steps, true),
+            Self::Day07 => legacy(case, steps, false),
+            Self::Day09 => shared(case, steps),
+        }
+    }
+}
+
+fn shared(case: &Case, steps: u64) -> Outcome {
+    let mut c = Computer::new(case.program.clone());
+    c.set_instruction_limit(Some(steps));
+    c.set_memory_limit(Some(MEMORY_LIMIT));
+    let mut input: VecDeque<i64> = case.input.iter().copied().collect();
+    let mut output = Vec::new();
+    let stop = match c.run_io(&mut input, &mut output) {
+        Ok(_) if c.halted() => Stop::Halted,
+        Ok(_) => Stop::AwaitingInput,
+        Err(IntcodeError::LimitExceeded {
+            limit: Limit::Instructions,
+            ..
+        }) => Stop::StepLimit,
+        Err(e) => Stop::Crashed(e.to_string()),
+    };
+    let memory = c.read_memory(0, case.program.len()).into_owned();
+    Outcome {
+        output,
+        memory,
+        stop,
+    }
+}
+
+fn legacy(case: &Case, steps: u64, repeat_input: bool) -> Outcome {
+    let mut data = case.program.clone();
+    let mut output = Vec::new();
+    let stop = match legacy_run(&mut data, &case.input, &mut output, steps, repeat_input) {
+        Ok(stop) => stop,
+        Err(e) => Stop::Crashed(e),
+    };
+    Outcome {
+        output,
+        memory: data,
+        stop,
+    }
+}
+
+// Reads `data[addr]`, failing where the old interpreters would have panicked indexing out of bounds
+fn get(data: &[i64], addr: i64) -> Result<i64, String> {
+    if addr < 0 || addr as usize >= data.len() {
+        return Err(format!("index {} out of bounds", addr));
+    }
+    Ok(data[addr as usize])
+}
+
+fn legacy_run(
+    data: &mut Vec<i64>,
+    input: &[i64],
+    output: &mut Vec<i64>,
+    steps: u64,
+    repeat_input: bool,
+) -> Result<Stop, String> {
+    let mut offset = 0;
+    let mut next_input = 0;
+    for _ in 0..steps {
+        let word = get(data, offset)?;
+        if !(0..100_000).contains(&word) {
+            return Err(format!("Unexpected opcode {} at {}", word, offset));
+        }
+        let modes = [word / 100 % 10, word / 1000 % 10];
+        let param = |data: &[i64], i: usize| -> Result<i64, String> {
+            let raw = get(data, offset + 1 + i as i64)?;
+            if i < 2 && modes[i] == 0 {
+                get(data, raw)
+            } else {
+                Ok(raw)
+            }
+        };
+        // the result of arithmetic and comparison instructions always goes to the position in their third word
+        let store = |data: &mut Vec<i64>, val: i64| -> Result<(), String> {
+            let addr = get(data, offset + 3)?;
+            get(data, addr)?;
+            data[addr as usize] = val;
+            Ok(())
+        };
+
+        offset = match word % 100 {
+            1 => {
+                let sum = param(data, 0)?.checked_add(param(data, 1)?);
+                store(data, sum.ok_or("attempt to add with overflow")?)?;
+                offset + 4
+            }
+            2 => {
+                let product = param(data, 0)?.checked_mul(param(data, 1)?);
+                store(data, product.ok_or("attempt to multiply with overflow")?)?;
+                offset + 4
+            }
+            3 => {
+                let val = if repeat_input {
+                    // day 5 read one value from stdin and stored it at every `in`
+                    match input.first() {
+                        Some(&val) => val,
+                        None => return Err("no input".to_string()),
+                    }
+                } else {
+                    match input.get(next_input) {
+                        Some(&val) => val,
+                        None => return Ok(Stop::AwaitingInput),
+                    }
+                };
+                next_input += 1;
+                let addr = get(data, offset + 1)?;
+                get(data, addr)?;
+                data[addr as usize] = val;
+                offset + 2
+            }
+            4 => {
+                output.push(param(data, 0)?);
+                offset + 2
+            }
+            5 if param(data, 0)? != 0 => param(data, 1)?,
+            6 if param(data, 0)? == 0 => param(data, 1)?,
+            5 | 6 => offset + 3,
+            7 => {
+                store(data, i64::from(param(data, 0)? < param(data, 1)?))?;
+                offset + 4
+            }
+            8 => {
+                store(data, i64::from(param(data, 0)? == param(data, 1)?))?;
+                offset + 4
+            }
+            99 => return Ok(Stop::Halted),
+            _ => return Err(format!("Unexpected opcode {} at {}", word, offset)),
+        };
+    }
+    Ok(Stop::StepLimit)
+}
+
+// Which parts of the outcomes don't all agree
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Mismatch {
+    pub output: bool,
+    pub memory: bool,
+    pub stop: bool,
+}
+
+impl Mismatch {
+    pub fn of(outcomes: &[Outcome]) -> Self {
+        let mut mismatch = Self::default();
+        for pair in outcomes.windows(2) {
+            mismatch.output |= pair[0].output != pair[1].output;
+            mismatch.memory |= pair[0].memory != pair[1].memory;
+            mismatch.stop |= !pair[0].stop.same(&pair[1].stop);
+        }
+        mismatch
+    }
+
+    pub fn any(self) -> bool {
+        self.output || self.memory || self.stop
+    }
+}
+
+// A case the interpreters disagree on, with what each of them did
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    pub case: Case,
+    pub mismatch: Mismatch,
+    pub outcomes: Vec<(Interpreter, Outcome)>,
+}
+
+impl Divergence {
+    // Runs `case` on every interpreter, returning how they disagree if they do
+    pub fn check(case: &Case, interpreters: &[Interpreter], steps: u64) -> Option<Self> {
+        let outcomes: Vec<Outcome> = interpreters.iter().map(|i| i.run(case, steps)).collect();
+        let mismatch = Mismatch::of(&outcomes);
+        if !mismatch.any() {
+            return None;
+        }
+        Some(Self {
+            case: case.clone(),
+            mismatch,
+            outcomes: interpreters.iter().copied().zip(outcomes).collect(),
+        })
+    }
+}
+
+// Shrinks the case of `divergence` while the interpreters keep disagreeing in the same way,
+// by cutting input, dropping runs of up to 4 words, and replacing words with `hlt` or smaller values
+pub fn minimize(divergence: &Divergence, interpreters: &[Interpreter], steps: u64) -> Divergence {
+    let mut best = divergence.clone();
+    let try_case =
+        |case: Case, best: &mut Divergence| match Divergence::check(&case, interpreters, steps) {
+            Some(d) if d.mismatch == best.mismatch && d.case != best.case => {
+                *best = d;
+                true
+            }
+            _ => false,
+        };
+
+    loop {
+        let mut shrunk = false;
+
+        while !best.case.input.is_empty() {
+            let mut case = best.case.clone();
+            case.input.pop();
+            if !try_case(case, &mut best) {
+                break;
+            }
+            shrunk = true;
+        }
+
+        for len in (1..=4).rev() {
+            let mut i = 0;
+            while i + len <= best.case.program.len() {
+                let mut case = best.case.clone();
+                case.program.drain(i..i + len);
+                if try_case(case, &mut best) {
+                    shrunk = true;
+                } else {
+                    i += 1;
+                }
+            }
+        }
+
+        for i in 0..best.case.program.len() {
+            let word = best.case.program[i];
+            for &smaller in &[99, 0, 1, word / 2] {
+                if cost(smaller) >= cost(word) {
+                    continue;
+                }
+                let mut case = best.case.clone();
+                case.program[i] = smaller;
+                if try_case(case, &mut best) {
+                    shrunk = true;
+                    break;
+                }
+            }
+        }
+        for i in 0..best.case.input.len() {
+            let val = best.case.input[i];
+            for &smaller in &[0, val / 2] {
+                if smaller.abs() >= val.abs() {
+                    continue;
+                }
+                let mut case = best.case.clone();
+                case.input[i] = smaller;
+                if try_case(case, &mut best) {
+                    shrunk = true;
+                    break;
+                }
+            }
+        }
+
+        if !shrunk {
+            return best;
+        }
+    }
+}
+
+// How far a word is from the simplest, `hlt` and then values closest to zero
+fn cost(word: i64) -> u64 {
+    match word {
+        99 => 0,
+        _ => 1 + word.unsigned_abs(),
+    }
+}
+
+// Generates `cases` programs from `seed`, returning a minimized reproducer for each one the interpreters disagree on
+pub fn fuzz(
+    seed: u64,
+    cases: usize,
+    features: Features,
+    interpreters: &[Interpreter],
+    steps: u64,
+) -> Vec<Divergence> {
+    let mut rng = Rng::new(seed);
+    let mut found = Vec::new();
+    for _ in 0..cases {
+        let case = generate(&mut rng, features);
+        if let Some(divergence) = Divergence::check(&case, interpreters, steps) {
+            found.push(minimize(&divergence, interpreters, steps));
+        }
+    }
+    found
+}
+
+fn join(vals: &[i64]) -> String {
+    let vals: Vec<String> = vals.iter().map(i64::to_string).collect();
+    vals.join(",")
+}
+
+// Program and input as comma separated lines followed by what each interpreter did
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "program: {}", join(&self.case.program))?;
+        writeln!(f, "input: {}", join(&self.case.input))?;
+        for (interpreter, outcome) in &self.outcomes {
+            let stop = match &outcome.stop {
+                Stop::Halted => "halted".to_string(),
+                Stop::AwaitingInput => "awaiting input".to_string(),
+                Stop::StepLimit => "step limit".to_string(),
+                Stop::Crashed(e) => format!("crashed: {}", e),
+            };
+            write!(f, "  {}: {}", interpreter.name(), stop)?;
+            if self.mismatch.output {
+                write!(f, ", output {}", join(&outcome.output))?;
+            }
+            if self.mismatch.memory {
+                write!(f, ", memory {}", join(&outcome.memory))?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STEPS: u64 = 1000;
+
+    #[test]
+    fn rng() {
+        let mut a = Rng::new(7);
+        let mut b = Rng::new(7);
+        for _ in 0..100 {
+            let val = a.range(-3..4);
+            assert!((-3..4).contains(&val));
+            assert_eq!(val, b.range(-3..4));
+        }
+        assert_ne!(Rng::new(0).next_u64(), Rng::new(1).next_u64());
+    }
+
+    #[test]
+    fn generated_programs_are_valid() {
+        let mut rng = Rng::new(1);
+        for _ in 0..200 {
+            let case = generate(&mut rng, Features::ALL);
+            let (starts, _) = crate::disasm::analyze(&case.program);
+            assert!(starts.contains(&0));
+        }
+    }
+
+    #[test]
+    fn legacy_features_agree() {
+        let divergences = fuzz(
+            2,
+            500,
+            Features::LEGACY,
+            &[Interpreter::Day07, Interpreter::Day09],
+            STEPS,
+        );
+        assert!(divergences.is_empty(), "{}", divergences[0]);
+    }
+
+    #[test]
+    fn all_features_diverge() {
+        let divergences = fuzz(3, 200, Features::ALL, &Interpreter::ALL, STEPS);
+        assert!(!divergences.is_empty());
+        for d in &divergences {
+            let rerun = Divergence::check(&d.case, &Interpreter::ALL, STEPS);
+            assert_eq!(Some(d), rerun.as_ref());
+        }
+    }
+
+    #[test]
+    fn read_past_end() {
+        // reading past the end of memory is a crash for the old interpreters but 0 for `Computer`
+        let case = Case {
+            program: vec![4, 10, 99],
+            input: vec![],
+        };
+        let divergence = Divergence::check(&case, &Interpreter::ALL, STEPS).unwrap();
+        assert_eq!(
+            "program: 4,10,99
+input: 
+  day05: crashed: index 10 out of bounds, output 
+  day07: crashed: index 10 out of bounds, output 
+  day09: halted, output 0
+",
+            divergence.to_string()
+        );
+    }
+
+    #[test]
+    fn day05_input() {
+        // day 5 stores its first input at every `in`, day 7 reads each in turn
+        let case = Case {
+            program: vec![3, 9, 3, 10, 4, 9, 4, 10, 99, 0, 0],
+            input: vec![5, 6],
+        };
+        assert_eq!(vec![5, 5], Interpreter::Day05.run(&case, STEPS).output);
+        assert_eq!(vec![5, 6], Interpreter::Day07.run(&case, STEPS).output);
+        assert_eq!(vec![5, 6], Interpreter::Day09.run(&case, STEPS).output);
+    }
+
+    #[test]
+    fn minimize_input_mode() {
+        // day 7 writes input to [3] despite the immediate mode, day 9 rejects it
+        let case = Case {
+            program: vec![1101, 2, 3, 9, 103, 11, 4, 9, 99, 0, 0, 0],
+            input: vec![5, 6],
+        };
+        let interpreters = [Interpreter::Day07, Interpreter::Day09];
+        let divergence = Divergence::check(&case, &interpreters, STEPS).unwrap();
+        let minimal = minimize(&divergence, &interpreters, STEPS);
+        assert_eq!(vec![103, 0, 4, 0, 99], minimal.case.program);
+        assert_eq!(vec![0], minimal.case.input);
+        assert_eq!(
+            "program: 103,0,4,0,99
+input: 0
+  day07: halted, output 0, memory 0,0,4,0,99
+  day09: crashed: Unsupported immediate mode write at 0, output , memory 103,0,4,0,99
+",
+            minimal.to_string()
+        );
+    }
+}
@@ -0,0 +1,104 @@
+use std::collections::VecDeque;
+use std::io::{self, prelude::*};
+use std::sync::mpsc::{Receiver, Sender};
+
+// Source of values for input instructions, used with `Computer::run_io`
+pub trait Input<T = i64> {
+    // Returns `None` when no value is available, which leaves the computer awaiting input
+    fn read(&mut self) -> Option<T>;
+}
+
+// Destination for values from output instructions, used with `Computer::run_io`
+pub trait Output<T = i64> {
+    fn write(&mut self, val: T);
+}
+
+impl<T> Input<T> for VecDeque<T> {
+    fn read(&mut self) -> Option<T> {
+        self.pop_front()
+    }
+}
+
+impl<T> Output<T> for VecDeque<T> {
+    fn write(&mut self, val: T) {
+        self.push_back(val);
+    }
+}
+
+impl<T> Output<T> for Vec<T> {
+    fn write(&mut self, val: T) {
+        self.push(val);
+    }
+}
+
+impl<T, F: FnMut() -> Option<T>> Input<T> for F {
+    fn read(&mut self) -> Option<T> {
+        self()
+    }
+}
+
+impl<T, F: FnMut(T)> Output<T> for F {
+    fn write(&mut self, val: T) {
+        self(val)
+    }
+}
+
+// Blocks until a value arrives, only returning `None` once every sender is dropped
+impl<T> Input<T> for Receiver<T> {
+    fn read(&mut self) -> Option<T> {
+        self.recv().ok()
+    }
+}
+
+// Values sent after the receiver is dropped are discarded
+impl<T> Output<T> for Sender<T> {
+    fn write(&mut self, val: T) {
+        let _ = self.send(val);
+    }
+}
+
+// Reads stdin a line at a time, feeding each character's code followed by a newline (10)
+#[derive(Debug, Default)]
+pub struct AsciiStdin {
+    buf: VecDeque<i64>,
+}
+
+impl AsciiStdin {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Input for AsciiStdin {
+    fn read(&mut self) -> Option<i64> {
+        if self.buf.is_empty() {
+            if let Err(e) = io::stdout().flush() {
+                panic!("{}", e);
+            }
+            let mut line = String::new();
+            match io::stdin().read_line(&mut line) {
+                Ok(0) => return None,
+                Ok(_) => {}
+                Err(e) => panic!("{}", e),
+            }
+            let line = line.trim_end_matches(['\n', '\r']);
+            self.buf.extend(line.bytes().map(i64::from));
+            self.buf.push_back(10);
+        }
+        self.buf.pop_front()
+    }
+}
+
+// Prints values below 128 as ASCII characters and anything else as a number on its own line
+#[derive(Debug, Default)]
+pub struct AsciiStdout;
+
+impl Output for AsciiStdout {
+    fn write(&mut self, val: i64) {
+        if (0..128).contains(&val) {
+            print!("{}", val as u8 as char);
+        } else {
+            println!("{}", val);
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use std::collections::VecDeque;
+
+// Everything needed to undo one executed instruction
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct JournalEntry<T> {
+    pub offset: usize,
+    pub relative_offset: i64,
+    pub halted: bool,
+    pub write: Option<(usize, T, usize)>, // (address, previous value, previous memory size)
+    pub input: Option<T>,                 // value consumed by an input instruction
+}
+
+// Undo log of executed instructions, oldest first
+// With a capacity only the most recent `capacity` instructions are kept
+#[derive(Clone, Debug)]
+pub(crate) struct Journal<T> {
+    pub entries: VecDeque<JournalEntry<T>>,
+    pub capacity: Option<usize>,
+}
+
+impl<T> Journal<T> {
+    pub fn new(capacity: Option<usize>) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    pub fn push(&mut self, entry: JournalEntry<T>) {
+        if self.capacity == Some(0) {
+            return;
+        }
+        if Some(self.entries.len()) == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+}
@@ -0,0 +1,1363 @@
+#![allow(clippy::cast_possible_truncation)]
+#![allow(clippy::cast_sign_loss)]
+
+mod bigint;
+mod cell;
+pub mod cfg;
+pub mod disasm;
+mod error;
+pub mod exec;
+pub mod fuzz;
+mod io;
+mod journal;
+mod limits;
+mod memory;
+pub mod network;
+pub mod phase;
+mod pool;
+mod profile;
+pub mod program;
+#[cfg(test)]
+mod properties;
+mod snapshot;
+pub mod solve;
+pub mod symbolic;
+mod trace;
+mod varint;
+
+pub use bigint::{BigInt, ParseBigIntError};
+pub use cell::{Cell, Overflow};
+pub use error::IntcodeError;
+pub use io::{AsciiStdin, AsciiStdout, Input, Output};
+use journal::{Journal, JournalEntry};
+use limits::Limits;
+pub use limits::{CancelToken, Limit};
+pub use memory::{DenseMemory, Memory, PagedMemory, PAGE_SIZE};
+pub use profile::Profile;
+pub use snapshot::Snapshot;
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+pub use trace::Trace;
+use trace::Tracer;
+
+// (opcode, mnemonic, parameter count, whether the last parameter is written to) for every instruction
+pub const OPCODES: [(i64, &str, usize, bool); 10] = [
+    (1, "add", 3, true),
+    (2, "mul", 3, true),
+    (3, "in", 1, true),
+    (4, "out", 1, false),
+    (5, "jt", 2, false),
+    (6, "jf", 2, false),
+    (7, "lt", 3, true),
+    (8, "eq", 3, true),
+    (9, "arb", 1, false),
+    (99, "hlt", 0, false),
+];
+
+// Reason `Computer::step_until_event` stopped executing
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RunState<T = i64> {
+    Halted,
+    AwaitingInput,
+    Output(T),
+}
+
+pub struct Computer<M: Memory = DenseMemory> {
+    memory: M,
+    memory_limit: Option<usize>,
+    limits: Limits,
+    executed: u64, // instructions executed so far
+    overflow: Overflow,
+    offset: usize,
+    relative_offset: i64,
+    halted: bool,
+    input: VecDeque<M::Cell>,
+    tracer: Option<Tracer<M::Cell>>,
+    trace: Option<Trace<M::Cell>>, // record of the instruction currently executing, only kept while tracing
+    journal: Option<Journal<M::Cell>>,
+    entry: Option<JournalEntry<M::Cell>>, // undo record of the instruction currently executing, only kept while journaling
+    profile: Option<Profile>,
+}
+
+impl Computer {
+    pub fn new(data: Vec<i64>) -> Self {
+        Self::with_memory(DenseMemory::from(data))
+    }
+
+    pub fn new_with_input(data: Vec<i64>, init_input: &[i64]) -> Self {
+        let mut c = Self::new(data);
+        c.push_input(init_input);
+        c
+    }
+}
+
+// Clones run untraced and unprofiled, the tracer and profile stay with the original
+impl<M: Memory + Clone> Clone for Computer<M> {
+    fn clone(&self) -> Self {
+        Self {
+            memory: self.memory.clone(),
+            memory_limit: self.memory_limit,
+            limits: self.limits.clone(),
+            executed: self.executed,
+            overflow: self.overflow,
+            offset: self.offset,
+            relative_offset: self.relative_offset,
+            halted: self.halted,
+            input: self.input.clone(),
+            tracer: None,
+            trace: None,
+            journal: self.journal.clone(),
+            entry: None,
+            profile: None,
+        }
+    }
+}
+
+impl<M: Memory<Cell = i64> + Default> Computer<M> {
+    // Resumes a computer from the state captured in `snapshot` with `memory_limit` set, failing with
+    // `IntcodeError::MemoryLimit` if restoring its memory would already need more words than that,
+    // so a snapshot with a corrupt or huge size can't exhaust memory
+    pub fn from_snapshot(
+        snapshot: Snapshot,
+        memory_limit: Option<usize>,
+    ) -> Result<Self, IntcodeError> {
+        let mut memory = M::default();
+        let offset = snapshot.offset;
+        let mut set = |pos: usize, val: i64| {
+            if memory_limit.is_some_and(|limit| memory.footprint_with(pos) > limit) {
+                return Err(IntcodeError::MemoryLimit {
+                    address: pos,
+                    offset,
+                });
+            }
+            memory.set(pos, val);
+            Ok(())
+        };
+        if snapshot.size > 0 {
+            set(snapshot.size - 1, 0)?;
+        }
+        for (start, words) in snapshot.runs {
+            for (pos, val) in (start..).zip(words) {
+                set(pos, val)?;
+            }
+        }
+        let mut c = Self::with_memory(memory);
+        c.offset = snapshot.offset;
+        c.relative_offset = snapshot.relative_offset;
+        c.halted = snapshot.halted;
+        c.input = snapshot.input.into();
+        c.set_memory_limit(memory_limit);
+        Ok(c)
+    }
+}
+
+impl<M: Memory<Cell = i64>> Computer<M> {
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            size: self.memory.size(),
+            runs: snapshot::runs(&self.memory.populated()),
+            offset: self.offset,
+            relative_offset: self.relative_offset,
+            input: self.input.iter().copied().collect(),
+            halted: self.halted,
+        }
+    }
+}
+
+impl<T: Cell> Computer<DenseMemory<T>> {
+    // Contents of memory up to the highest address in the program or written since,
+    // only offered for dense memory where that can't be far more than was ever written
+    pub fn data(&self) -> &[T] {
+        self.memory.as_slice()
+    }
+}
+
+impl<M: Memory> Computer<M> {
+    // Creates a computer running the program already loaded into `memory`
+    pub fn with_memory(memory: M) -> Self {
+        Self {
+            memory,
+            memory_limit: None,
+            limits: Limits::default(),
+            executed: 0,
+            overflow: Overflow::default(),
+            offset: 0,
+            relative_offset: 0,
+            halted: false,
+            input: VecDeque::new(),
+            tracer: None,
+            trace: None,
+            journal: None,
+            entry: None,
+            profile: None,
+        }
+    }
+
+    // Makes writes fail with `IntcodeError::MemoryLimit` once memory would need more than `limit` words
+    pub fn set_memory_limit(&mut self, limit: Option<usize>) {
+        self.memory_limit = limit;
+    }
+
+    // Makes execution fail with `IntcodeError::LimitExceeded` once `limit` instructions have been executed in total
+    pub fn set_instruction_limit(&mut self, limit: Option<u64>) {
+        self.limits.instructions = limit;
+    }
+
+    // Makes execution fail with `IntcodeError::LimitExceeded` after `deadline`
+    // The clock is only checked every so often, so a few more instructions may run past it
+    pub fn set_deadline(&mut self, deadline: Option<Instant>) {
+        self.limits.deadline = deadline;
+    }
+
+    // Sets a deadline `timeout` from now
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.set_deadline(Some(Instant::now() + timeout));
+    }
+
+    // Makes execution fail with `IntcodeError::LimitExceeded` once `token` is cancelled
+    pub fn set_cancel_token(&mut self, token: Option<CancelToken>) {
+        self.limits.cancel = token;
+    }
+
+    // Sets what happens when `add` or `mul` overflows the cell type, `Overflow::Error` by default
+    pub fn set_overflow(&mut self, overflow: Overflow) {
+        self.overflow = overflow;
+    }
+
+    // Number of instructions executed so far, not counting ones undone with `rewind`
+    pub fn instructions(&self) -> u64 {
+        self.executed
+    }
+
+    pub fn run(&mut self, new_input: &[M::Cell]) -> Result<Vec<M::Cell>, IntcodeError> {
+        self.push_input(new_input);
+        let mut output = Vec::new();
+
+        while let RunState::Output(val) = self.step_until_event()? {
+            output.push(val);
+        }
+
+        Ok(output)
+    }
+
+    // Queues `new_input` to be consumed by input instructions
+    pub fn push_input(&mut self, new_input: &[M::Cell]) {
+        self.input.extend(new_input.iter().cloned());
+    }
+
+    // Runs until halted, pulling values from `input` whenever queued input runs out and sending every output to `output`
+    // Returns `RunState::AwaitingInput` if `input` has nothing to give
+    pub fn run_io<I: Input<M::Cell> + ?Sized, O: Output<M::Cell> + ?Sized>(
+        &mut self,
+        input: &mut I,
+        output: &mut O,
+    ) -> Result<RunState<M::Cell>, IntcodeError> {
+        loop {
+            match self.step_until_event()? {
+                RunState::Output(val) => output.write(val),
+                RunState::AwaitingInput => match input.read() {
+                    Some(val) => self.input.push_back(val),
+                    None => return Ok(RunState::AwaitingInput),
+                },
+                RunState::Halted => return Ok(RunState::Halted),
+            }
+        }
+    }
+
+    // Like `run_io` but awaits `input` whenever queued input runs out, letting other tasks on the `exec::Executor` run
+    // Returns `RunState::AwaitingInput` once `input` is empty and every sender to it is dropped
+    pub async fn run_async<O: Output<M::Cell> + ?Sized>(
+        &mut self,
+        input: &mut exec::Receiver<M::Cell>,
+        output: &mut O,
+    ) -> Result<RunState<M::Cell>, IntcodeError> {
+        loop {
+            match self.step_until_event()? {
+                RunState::Output(val) => output.write(val),
+                RunState::AwaitingInput => match input.recv().await {
+                    Some(val) => self.input.push_back(val),
+                    None => return Ok(RunState::AwaitingInput),
+                },
+                RunState::Halted => return Ok(RunState::Halted),
+            }
+        }
+    }
+
+    // Runs until the program outputs a value, needs input that hasn't been queued, or halts
+    // When awaiting input the input instruction is not consumed, so running again after `push_input` resumes it
+    pub fn step_until_event(&mut self) -> Result<RunState<M::Cell>, IntcodeError> {
+        loop {
+            if let Some(state) = self.step()? {
+                return Ok(state);
+            }
+        }
+    }
+
+    // Executes a single instruction, returning the event it produced if any
+    pub fn step(&mut self) -> Result<Option<RunState<M::Cell>>, IntcodeError> {
+        if let Some(limit) = self.limits.exceeded(self.executed) {
+            return Err(IntcodeError::LimitExceeded {
+                limit,
+                offset: self.offset,
+            });
+        }
+        let instruction = self.read(self.offset);
+        let (opcode, modes) = match instruction.to_i64().and_then(decode) {
+            Some(decoded) => decoded,
+            None => {
+                return Err(IntcodeError::UnknownOpcode {
+                    opcode: cell::clamp(&instruction),
+                    offset: self.offset,
+                })
+            }
+        };
+        if self.tracer.is_some() {
+            self.trace = Some(Trace::new(self.offset, opcode, modes));
+        }
+        if self.journal.is_some() {
+            self.entry = Some(JournalEntry {
+                offset: self.offset,
+                relative_offset: self.relative_offset,
+                halted: self.halted,
+                write: None,
+                input: None,
+            });
+        }
+        let (offset, state) = match opcode {
+            1 => {
+                // add
+                self.add(modes)?;
+                (self.offset + 4, None)
+            }
+            2 => {
+                // multiply
+                self.mul(modes)?;
+                (self.offset + 4, None)
+            }
+            3 => {
+                // input
+                let val = match self.input.pop_front() {
+                    Some(val) => val,
+                    None => {
+                        self.trace = None;
+                        self.entry = None;
+                        return Ok(Some(RunState::AwaitingInput));
+                    }
+                };
+                if let Err(e) = self.write(self.offset + 1, val.clone(), modes[0]) {
+                    self.input.push_front(val);
+                    return Err(e);
+                }
+                if let Some(entry) = &mut self.entry {
+                    entry.input = Some(val);
+                }
+                (self.offset + 2, None)
+            }
+            4 => {
+                // output
+                let val = self.output(modes)?;
+                (self.offset + 2, Some(RunState::Output(val)))
+            }
+            5 => {
+                // jump if true
+                (self.jit(modes)?, None)
+            }
+            6 => {
+                // jump if false
+                (self.jif(modes)?, None)
+            }
+            7 => {
+                // less than
+                self.lt(modes)?;
+                (self.offset + 4, None)
+            }
+            8 => {
+                // equals
+                self.eq(modes)?;
+                (self.offset + 4, None)
+            }
+            9 => {
+                // set relative
+                self.rel(modes)?;
+                (self.offset + 2, None)
+            }
+            99 => {
+                self.halted = true;
+                (self.offset, Some(RunState::Halted))
+            }
+            _ => {
+                return Err(IntcodeError::UnknownOpcode {
+                    opcode: cell::clamp(&instruction),
+                    offset: self.offset,
+                })
+            }
+        };
+        if let Some(profile) = &mut self.profile {
+            profile.executed(self.offset, opcode, offset);
+        }
+        self.offset = offset;
+        self.executed += 1;
+
+        if let (Some(trace), Some(tracer)) = (self.trace.take(), &mut self.tracer) {
+            tracer(&trace);
+        }
+        if let (Some(entry), Some(journal)) = (self.entry.take(), &mut self.journal) {
+            journal.push(entry);
+        }
+
+        Ok(state)
+    }
+
+    // Calls `tracer` with a `Trace` after every executed instruction
+    pub fn set_tracer<F: FnMut(&Trace<M::Cell>) + Send + 'static>(&mut self, tracer: F) {
+        self.tracer = Some(Box::new(tracer));
+    }
+
+    pub fn clear_tracer(&mut self) {
+        self.tracer = None;
+    }
+
+    // Starts counting executed instructions and memory accesses from scratch, see `Profile`
+    pub fn enable_profiling(&mut self) {
+        self.profile = Some(Profile::new(self.offset));
+    }
+
+    // Stops profiling, returning everything counted since it was enabled
+    pub fn take_profile(&mut self) -> Option<Profile> {
+        self.profile.take()
+    }
+
+    pub fn profile(&self) -> Option<&Profile> {
+        self.profile.as_ref()
+    }
+
+    // Starts recording every executed instruction so it can be undone with `rewind`
+    // With a `capacity` only the most recent `capacity` instructions can be undone
+    pub fn enable_journal(&mut self, capacity: Option<usize>) {
+        self.journal = Some(Journal::new(capacity));
+    }
+
+    pub fn disable_journal(&mut self) {
+        self.journal = None;
+    }
+
+    // Number of instructions that can currently be undone
+    pub fn journal_len(&self) -> usize {
+        self.journal.as_ref().map_or(0, |j| j.entries.len())
+    }
+
+    // Undoes up to `steps` instructions, returning how many were undone
+    // Consumed input is pushed back but output that was already returned can't be taken back
+    pub fn rewind(&mut self, steps: usize) -> usize {
+        let journal = match &mut self.journal {
+            Some(journal) => journal,
+            None => return 0,
+        };
+        for i in 0..steps {
+            let entry = match journal.entries.pop_back() {
+                Some(entry) => entry,
+                None => return i,
+            };
+            if let Some((pos, val, size)) = entry.write {
+                self.memory.set(pos, val);
+                self.memory.truncate(size);
+            }
+            if let Some(val) = entry.input {
+                self.input.push_front(val);
+            }
+            self.offset = entry.offset;
+            self.relative_offset = entry.relative_offset;
+            self.halted = entry.halted;
+            self.executed = self.executed.saturating_sub(1);
+        }
+        steps
+    }
+
+    // Rewinds to just before the most recent journaled instruction that wrote to `address`,
+    // returning the number of instructions undone, or `None` without rewinding if there is no such write
+    pub fn rewind_to_write(&mut self, address: usize) -> Option<usize> {
+        let entries = &self.journal.as_ref()?.entries;
+        let steps = entries
+            .iter()
+            .rev()
+            .position(|e| matches!(e.write, Some((pos, _, _)) if pos == address))?;
+        Some(self.rewind(steps + 1))
+    }
+
+    // Contents of memory from `start` to `start + len`, stopping at the highest address in the program or written since
+    pub fn read_memory(&self, start: usize, len: usize) -> Cow<'_, [M::Cell]> {
+        self.memory.read(start, len)
+    }
+
+    pub fn memory(&self) -> &M {
+        &self.memory
+    }
+
+    // Overwrites memory at `pos` directly, ignoring any memory limit
+    pub fn set_memory(&mut self, pos: usize, val: M::Cell) {
+        self.memory.set(pos, val);
+    }
+
+    pub fn halted(&self) -> bool {
+        self.halted
+    }
+
+    // Number of queued input values not yet consumed
+    pub fn pending_input(&self) -> usize {
+        self.input.len()
+    }
+
+    // Instruction pointer
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    // Relative base used by mode 2 parameters
+    pub fn relative_offset(&self) -> i64 {
+        self.relative_offset
+    }
+
+    // Gets the value of the param stored at `offset` according to `mode`
+    fn get_param(&mut self, offset: usize, mode: i64) -> Result<M::Cell, IntcodeError> {
+        let param = match mode {
+            0 => {
+                // position
+                let p = self.address(&self.read(offset), 0)?;
+                self.read_param(p)
+            }
+            1 => self.read(offset), // immediate
+            2 => {
+                // relative
+                let p = self.address(&self.read(offset), self.relative_offset)?;
+                self.read_param(p)
+            }
+            _ => {
+                return Err(IntcodeError::InvalidMode {
+                    mode,
+                    offset: self.offset,
+                })
+            }
+        };
+        if let Some(trace) = &mut self.trace {
+            trace.operands.push(param.clone());
+        }
+        Ok(param)
+    }
+
+    // Gets the first two param values of the current instruction
+    fn get_params(&mut self, modes: [i64; 3]) -> Result<(M::Cell, M::Cell), IntcodeError> {
+        let a = self.get_param(self.offset + 1, modes[0])?;
+        let b = self.get_param(self.offset + 2, modes[1])?;
+        Ok((a, b))
+    }
+
+    fn write(&mut self, offset: usize, val: M::Cell, mode: i64) -> Result<(), IntcodeError> {
+        let base = match mode {
+            0 => 0,
+            1 => {
+                return Err(IntcodeError::ImmediateWrite {
+                    offset: self.offset,
+                })
+            }
+            2 => self.relative_offset,
+            _ => {
+                return Err(IntcodeError::InvalidMode {
+                    mode,
+                    offset: self.offset,
+                })
+            }
+        };
+        let pos = self.address(&self.read(offset), base)?;
+        if let Some(limit) = self.memory_limit {
+            if self.memory.footprint_with(pos) > limit {
+                return Err(IntcodeError::MemoryLimit {
+                    address: pos,
+                    offset: self.offset,
+                });
+            }
+        }
+        if let Some(entry) = &mut self.entry {
+            entry.write = Some((pos, self.memory.get(pos), self.memory.size()));
+        }
+        if let Some(trace) = &mut self.trace {
+            trace.writes.push((pos, val.clone()));
+        }
+        self.memory.set(pos, val);
+        if let Some(profile) = &mut self.profile {
+            profile.write(pos);
+        }
+        Ok(())
+    }
+
+    fn read(&self, pos: usize) -> M::Cell {
+        self.memory.get(pos)
+    }
+
+    // Reads a parameter through its address, counting it when profiling
+    fn read_param(&mut self, pos: usize) -> M::Cell {
+        if let Some(profile) = &mut self.profile {
+            profile.read(pos);
+        }
+        self.memory.get(pos)
+    }
+
+    // Converts `pos` offset by `base` to an address in memory, rejecting negative addresses
+    fn address(&self, pos: &M::Cell, base: i64) -> Result<usize, IntcodeError> {
+        let pos = match pos.to_i64().and_then(|p| p.checked_add(base)) {
+            Some(pos) => pos,
+            None => {
+                return Err(IntcodeError::Overflow {
+                    offset: self.offset,
+                })
+            }
+        };
+        if pos < 0 {
+            Err(IntcodeError::NegativeAddress {
+                address: pos,
+                offset: self.offset,
+            })
+        } else {
+            Ok(pos as usize)
+        }
+    }
+
+    // Unwraps the result of `add` or `mul`, which is only `None` if it overflowed under `Overflow::Error`
+    fn checked(&self, val: Option<M::Cell>) -> Result<M::Cell, IntcodeError> {
+        val.ok_or(IntcodeError::Overflow {
+            offset: self.offset,
+        })
+    }
+
+    fn add(&mut self, modes: [i64; 3]) -> Result<(), IntcodeError> {
+        let (a, b) = self.get_params(modes)?;
+        let sum = self.checked(a.add(&b, self.overflow))?;
+        self.write(self.offset + 3, sum, modes[2])
+    }
+
+    fn mul(&mut self, modes: [i64; 3]) -> Result<(), IntcodeError> {
+        let (a, b) = self.get_params(modes)?;
+        let product = self.checked(a.mul(&b, self.overflow))?;
+        self.write(self.offset + 3, product, modes[2])
+    }
+
+    fn output(&mut self, modes: [i64; 3]) -> Result<M::Cell, IntcodeError> {
+        self.get_param(self.offset + 1, modes[0])
+    }
+
+    // if first param is non-zero, return 2nd param as new offset, otherwise do nothing (return current offset)
+    fn jit(&mut self, modes: [i64; 3]) -> Result<usize, IntcodeError> {
+        let (cond, target) = self.get_params(modes)?;
+
+        if cond == M::Cell::default() {
+            Ok(self.offset + 3)
+        } else {
+            self.address(&target, 0)
+        }
+    }
+
+    // if first param is zero, return 2nd param as new offset, otherwise advance as usual
+    fn jif(&mut self, modes: [i64; 3]) -> Result<usize, IntcodeError> {
+        let (cond, target) = self.get_params(modes)?;
+
+        if cond == M::Cell::default() {
+            self.address(&target, 0)
+        } else {
+            Ok(self.offset + 3)
+        }
+    }
+
+    fn lt(&mut self, modes: [i64; 3]) -> Result<(), IntcodeError> {
+        let (a, b) = self.get_params(modes)?;
+        self.write(self.offset + 3, cell::flag(a < b), modes[2])
+    }
+
+    fn eq(&mut self, modes: [i64; 3]) -> Result<(), IntcodeError> {
+        let (a, b) = self.get_params(modes)?;
+        self.write(self.offset + 3, cell::flag(a == b), modes[2])
+    }
+
+    fn rel(&mut self, modes: [i64; 3]) -> Result<(), IntcodeError> {
+        let val = self.get_param(self.offset + 1, modes[0])?;
+        self.relative_offset = match val
+            .to_i64()
+            .and_then(|v| self.relative_offset.checked_add(v))
+        {
+            Some(relative_offset) => relative_offset,
+            None => {
+                return Err(IntcodeError::Overflow {
+                    offset: self.offset,
+                })
+            }
+        };
+        Ok(())
+    }
+}
+
+// Splits `instruction` into its two digit opcode and the modes of its three params
+// Returns `None` for negative instructions and ones with more than three mode digits
+pub(crate) fn decode(instruction: i64) -> Option<(i64, [i64; 3])> {
+    if !(0..100_000).contains(&instruction) {
+        return None;
+    }
+    Some((
+        instruction % 100,
+        [
+            instruction / 100 % 10,
+            instruction / 1000 % 10,
+            instruction / 10_000 % 10,
+        ],
+    ))
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn eq_pos() {
+        let data = vec![3, 9, 8, 9, 10, 9, 4, 9, 99, -1, 8];
+
+        let mut eq = Computer::new(data.clone());
+        assert_eq!(Ok(vec![1]), eq.run(&[8]));
+
+        let mut neq = Computer::new(data);
+        assert_eq!(Ok(vec![0]), neq.run(&[9]));
+    }
+
+    #[test]
+    fn lt_pos() {
+        let data = vec![3, 9, 7, 9, 10, 9, 4, 9, 99, -1, 8];
+
+        let mut lt = Computer::new(data.clone());
+        assert_eq!(Ok(vec![1]), lt.run(&[7]));
+
+        let mut nlt = Computer::new(data);
+        assert_eq!(Ok(vec![0]), nlt.run(&[8]));
+    }
+
+    #[test]
+    fn eq_imm() {
+        let data = vec![3, 3, 1108, -1, 8, 3, 4, 3, 99];
+
+        let mut eq = Computer::new(data.clone());
+        assert_eq!(Ok(vec![1]), eq.run(&[8]));
+
+        let mut neq = Computer::new(data);
+        assert_eq!(Ok(vec![0]), neq.run(&[9]));
+    }
+
+    #[test]
+    fn lt_imm() {
+        let data = vec![3, 3, 1107, -1, 8, 3, 4, 3, 99];
+
+        let mut lt = Computer::new(data.clone());
+        assert_eq!(Ok(vec![1]), lt.run(&[7]));
+
+        let mut nlt = Computer::new(data);
+        assert_eq!(Ok(vec![0]), nlt.run(&[8]));
+    }
+
+    #[test]
+    fn amp1() {
+        let data = vec![
+            3, 15, 3, 16, 1002, 16, 10, 16, 1, 16, 15, 15, 4, 15, 99, 0, 0,
+        ];
+
+        let mut output = vec![0];
+        let phases = &[4, 3, 2, 1, 0];
+
+        for phase in phases {
+            let mut amp = Computer::new(data.clone());
+            output = amp.run(&[*phase, output[0]]).unwrap();
+        }
+
+        assert_eq!(vec![43210], output);
+    }
+
+    #[test]
+    fn amp2() {
+        let data = vec![
+            3, 23, 3, 24, 1002, 24, 10, 24, 1002, 23, -1, 23, 101, 5, 23, 23, 1, 24, 23, 23, 4, 23,
+            99, 0, 0,
+        ];
+
+        let mut output = vec![0];
+        let phases = &[0, 1, 2, 3, 4];
+
+        for phase in phases {
+            let mut amp = Computer::new(data.clone());
+            output = amp.run(&[*phase, output[0]]).unwrap();
+        }
+
+        assert_eq!(vec![54321], output);
+    }
+
+    #[test]
+    fn amp3() {
+        let data = vec![
+            3, 31, 3, 32, 1002, 32, 10, 32, 1001, 31, -2, 31, 1007, 31, 0, 33, 1002, 33, 7, 33, 1,
+            33, 31, 31, 1, 32, 31, 31, 4, 31, 99, 0, 0, 0,
+        ];
+
+        let mut output = vec![0];
+        let phases = &[1, 0, 4, 3, 2];
+
+        for phase in phases {
+            let mut amp = Computer::new(data.clone());
+            output = amp.run(&[*phase, output[0]]).unwrap();
+        }
+
+        assert_eq!(vec![65210], output);
+    }
+
+    #[test]
+    fn quine() {
+        let data = vec![
+            109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+        ];
+
+        let mut quine = Computer::new(data.clone());
+        assert_eq!(Ok(data), quine.run(&[]));
+    }
+
+    #[test]
+    #[allow(clippy::unreadable_literal)]
+    fn large() {
+        let data = vec![104, 1125899906842624, 99];
+
+        let mut c = Computer::new(data.clone());
+        assert_eq!(Ok(vec![1125899906842624]), c.run(&[]));
+    }
+
+    #[test]
+    fn unknown_opcode() {
+        let mut c = Computer::new(vec![1101, 1, 1, 5, 42, 0]);
+        assert_eq!(
+            Err(IntcodeError::UnknownOpcode {
+                opcode: 42,
+                offset: 4
+            }),
+            c.run(&[])
+        );
+    }
+
+    #[test]
+    fn invalid_mode() {
+        let mut c = Computer::new(vec![304, 0, 99]);
+        assert_eq!(
+            Err(IntcodeError::InvalidMode { mode: 3, offset: 0 }),
+            c.run(&[])
+        );
+    }
+
+    #[test]
+    fn immediate_write() {
+        let mut c = Computer::new(vec![11101, 1, 1, 0, 99]);
+        assert_eq!(Err(IntcodeError::ImmediateWrite { offset: 0 }), c.run(&[]));
+    }
+
+    #[test]
+    fn negative_address() {
+        let mut read = Computer::new(vec![4, -1, 99]);
+        assert_eq!(
+            Err(IntcodeError::NegativeAddress {
+                address: -1,
+                offset: 0
+            }),
+            read.run(&[])
+        );
+
+        let mut write = Computer::new(vec![109, -5, 203, 0, 99]);
+        assert_eq!(
+            Err(IntcodeError::NegativeAddress {
+                address: -5,
+                offset: 2
+            }),
+            write.run(&[1])
+        );
+
+        let mut jump = Computer::new(vec![1105, 1, -3]);
+        assert_eq!(
+            Err(IntcodeError::NegativeAddress {
+                address: -3,
+                offset: 0
+            }),
+            jump.run(&[])
+        );
+    }
+
+    #[test]
+    fn run_states() {
+        let data = vec![3, 9, 4, 9, 3, 9, 4, 9, 99, 0];
+
+        let mut c = Computer::new(data);
+        assert_eq!(Ok(RunState::AwaitingInput), c.step_until_event());
+        assert_eq!(Ok(RunState::AwaitingInput), c.step_until_event());
+        c.push_input(&[5]);
+        assert_eq!(Ok(RunState::Output(5)), c.step_until_event());
+        assert_eq!(Ok(RunState::AwaitingInput), c.step_until_event());
+        assert!(!c.halted());
+        c.push_input(&[7]);
+        assert_eq!(Ok(RunState::Output(7)), c.step_until_event());
+        assert_eq!(Ok(RunState::Halted), c.step_until_event());
+        assert!(c.halted());
+    }
+
+    #[test]
+    fn step() {
+        let data = vec![1101, 2, 3, 5, 104, 0, 99];
+
+        let mut c = Computer::new(data);
+        assert_eq!(Ok(None), c.step());
+        assert_eq!(4, c.offset());
+        assert_eq!(5, c.data()[5]);
+        assert_eq!(Ok(Some(RunState::Output(5))), c.step());
+        assert_eq!(Ok(Some(RunState::Halted)), c.step());
+        assert_eq!(6, c.offset());
+    }
+
+    #[test]
+    fn trace() {
+        use std::sync::{Arc, Mutex};
+
+        let data = vec![109, 3, 21101, 2, 3, 2, 99];
+        let traces = Arc::new(Mutex::new(Vec::new()));
+
+        let mut c = Computer::new(data);
+        let t = Arc::clone(&traces);
+        c.set_tracer(move |trace| t.lock().unwrap().push(trace.clone()));
+        c.run(&[]).unwrap();
+        assert_eq!(3, c.relative_offset());
+
+        let traces = traces.lock().unwrap();
+        assert_eq!(3, traces.len());
+        assert_eq!(
+            Trace {
+                offset: 0,
+                opcode: 9,
+                modes: [1, 0, 0],
+                operands: vec![3],
+                writes: vec![],
+            },
+            traces[0]
+        );
+        assert_eq!(
+            Trace {
+                offset: 2,
+                opcode: 1,
+                modes: [1, 1, 2],
+                operands: vec![2, 3],
+                writes: vec![(5, 5)],
+            },
+            traces[1]
+        );
+        assert_eq!(99, traces[2].opcode);
+    }
+
+    #[test]
+    fn oversized_instruction() {
+        let mut c = Computer::new(vec![100_001, 0, 0, 0, 99]);
+        assert_eq!(
+            Err(IntcodeError::UnknownOpcode {
+                opcode: 100_001,
+                offset: 0
+            }),
+            c.run(&[])
+        );
+    }
+
+    #[test]
+    fn decode_instruction() {
+        assert_eq!(Some((2, [0, 1, 0])), decode(1002));
+        assert_eq!(Some((99, [0, 0, 0])), decode(99));
+        assert_eq!(Some((1, [2, 1, 2])), decode(21201));
+        assert_eq!(None, decode(-1));
+        assert_eq!(None, decode(100_001));
+    }
+
+    #[test]
+    fn paged_memory() {
+        let data = vec![
+            109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+        ];
+
+        let mut quine = Computer::with_memory(PagedMemory::from(data.clone()));
+        assert_eq!(Ok(data), quine.run(&[]));
+
+        // write to and read back from a huge address
+        let mut c = Computer::with_memory(PagedMemory::from(vec![3i64, 1 << 40, 4, 1 << 40, 99]));
+        assert_eq!(Ok(vec![7]), c.run(&[7]));
+        assert_eq!(2 * PAGE_SIZE, c.memory().footprint_with(0));
+        assert_eq!(&[3, 1 << 40][..], &c.read_memory(0, 2)[..]);
+        assert_eq!(&[0, 7][..], &c.read_memory((1 << 40) - 1, 4)[..]);
+        let snapshot = c.snapshot();
+        assert_eq!(
+            vec![(0, vec![3, 1 << 40, 4, 1 << 40, 99]), (1 << 40, vec![7])],
+            snapshot.runs
+        );
+        let resumed: Computer<PagedMemory> =
+            Computer::from_snapshot(snapshot.clone(), Some(2 * PAGE_SIZE)).unwrap();
+        assert_eq!(snapshot, resumed.snapshot());
+        // dense memory would need every word up to the size, so the limit stops it before allocating
+        assert_eq!(
+            Err(IntcodeError::MemoryLimit {
+                address: 1 << 40,
+                offset: 4
+            }),
+            Computer::<DenseMemory>::from_snapshot(snapshot, Some(1 << 20)).map(|c| c.offset())
+        );
+        assert_eq!(2 * PAGE_SIZE, resumed.memory().footprint_with(0));
+        let analysis = symbolic::analyze(&c, &[]).unwrap();
+        assert_eq!(Some(symbolic::Expr::constant(7)), analysis.cell(1 << 40));
+    }
+
+    #[test]
+    fn memory_limit() {
+        let data = vec![3, 1000, 99];
+
+        let mut c = Computer::new(data.clone());
+        c.set_memory_limit(Some(1000));
+        assert_eq!(
+            Err(IntcodeError::MemoryLimit {
+                address: 1000,
+                offset: 0
+            }),
+            c.run(&[1])
+        );
+        assert_eq!(3, c.data().len());
+
+        let mut c = Computer::new(data.clone());
+        c.set_memory_limit(Some(1001));
+        assert_eq!(Ok(vec![]), c.run(&[1]));
+
+        let mut c = Computer::with_memory(PagedMemory::from(vec![3i64, 1 << 40, 99]));
+        c.set_memory_limit(Some(PAGE_SIZE));
+        assert_eq!(
+            Err(IntcodeError::MemoryLimit {
+                address: 1 << 40,
+                offset: 0
+            }),
+            c.run(&[1])
+        );
+    }
+
+    #[test]
+    fn streaming_io() {
+        use std::sync::mpsc;
+        use std::thread;
+
+        // doubles every input until it reads 0
+        let data = vec![
+            3, 15, 1006, 15, 14, 1002, 15, 2, 15, 4, 15, 1105, 1, 0, 99, 0,
+        ];
+
+        let mut c = Computer::new(data.clone());
+        let mut input: VecDeque<i64> = vec![1, 2].into();
+        let mut output = VecDeque::new();
+        assert_eq!(
+            Ok(RunState::AwaitingInput),
+            c.run_io(&mut input, &mut output)
+        );
+        input.push_back(3);
+        input.push_back(0);
+        assert_eq!(Ok(RunState::Halted), c.run_io(&mut input, &mut output));
+        assert_eq!(vec![2, 4, 6], Vec::from(output));
+
+        let mut c = Computer::new(data.clone());
+        let mut n = 3;
+        let mut count_down = || {
+            n -= 1;
+            Some(n)
+        };
+        let mut sum = 0;
+        assert_eq!(
+            Ok(RunState::Halted),
+            c.run_io(&mut count_down, &mut |val| sum += val)
+        );
+        assert_eq!(6, sum);
+
+        // chain two machines with channels, each on its own thread
+        let (tx_a, mut rx_a) = mpsc::channel();
+        let (mut tx_b, mut rx_b) = mpsc::channel();
+        let (mut tx_out, rx_out) = mpsc::channel();
+        let mut a = Computer::new(data.clone());
+        let mut b = Computer::new(data);
+        let a = thread::spawn(move || a.run_io(&mut rx_a, &mut tx_b));
+        let b = thread::spawn(move || b.run_io(&mut rx_b, &mut tx_out));
+        for val in &[1, 2, 0] {
+            tx_a.send(*val).unwrap();
+        }
+        assert_eq!(Ok(RunState::Halted), a.join().unwrap());
+        // `a` never forwards the 0, so `b` starves once `a`'s sender is dropped
+        assert_eq!(Ok(RunState::AwaitingInput), b.join().unwrap());
+        assert_eq!(vec![4, 8], rx_out.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn async_ring() {
+        // increments every input and passes it on, halting once it outputs 1000 or more
+        let data = vec![
+            3, 100, 1001, 100, 1, 100, 4, 100, 1007, 100, 1000, 101, 1005, 101, 0, 99,
+        ];
+
+        let mut executor = exec::Executor::new();
+        let (first_tx, mut rx) = exec::channel();
+        first_tx.send(0);
+        let mut handles = Vec::new();
+        for i in 0..100 {
+            let mut c = Computer::new(data.clone());
+            let (tx, next_rx) = if i == 99 {
+                (first_tx.clone(), exec::channel().1)
+            } else {
+                exec::channel()
+            };
+            let mut input = std::mem::replace(&mut rx, next_rx);
+            handles.push(executor.spawn(async move {
+                let mut last = 0;
+                let state = c
+                    .run_async(&mut input, &mut |val| {
+                        last = val;
+                        tx.send(val);
+                    })
+                    .await;
+                (state, last)
+            }));
+        }
+        drop(first_tx);
+
+        assert_eq!(0, executor.run());
+        assert_eq!(Some((Ok(RunState::Halted), 1000)), handles[99].take());
+        assert_eq!(Some((Ok(RunState::Halted), 1099)), handles[98].take());
+
+        // a machine whose senders are all dropped stops awaiting
+        let (tx, mut input) = exec::channel();
+        let mut c = Computer::new(data);
+        let handle = executor.spawn(async move {
+            let mut output = Vec::new();
+            let state = c.run_async(&mut input, &mut output).await;
+            (state, output)
+        });
+        tx.send(5);
+        assert_eq!(1, executor.run());
+        drop(tx);
+        assert_eq!(0, executor.run());
+        assert_eq!(Some((Ok(RunState::AwaitingInput), vec![6])), handle.take());
+    }
+
+    #[test]
+    fn fork_and_resume() {
+        // outputs the running total of its inputs until it reads 0
+        let data = vec![
+            109, 20, 3, 19, 1006, 19, 18, 20201, 0, 19, 0, 204, 0, 1105, 1, 2, 0, 0, 99, 0,
+        ];
+
+        let mut c = Computer::new(data);
+        assert_eq!(Ok(vec![1, 3]), c.run(&[1, 2]));
+
+        let mut fork = c.clone();
+        assert_eq!(Ok(vec![13]), fork.run(&[10, 0]));
+        assert!(fork.halted());
+
+        let mut buf = Vec::new();
+        c.snapshot().write_to(&mut buf).unwrap();
+        let mut resumed: Computer =
+            Computer::from_snapshot(Snapshot::read_from(&mut &buf[..]).unwrap(), None).unwrap();
+        let mut paged: Computer<PagedMemory> =
+            Computer::from_snapshot(c.snapshot(), Some(PAGE_SIZE)).unwrap();
+
+        assert_eq!(Ok(vec![6]), c.run(&[3]));
+        assert_eq!(Ok(vec![6]), resumed.run(&[3]));
+        assert_eq!(Ok(vec![6]), paged.run(&[3]));
+        assert_eq!(c.snapshot(), resumed.snapshot());
+        assert_eq!(c.snapshot(), paged.snapshot());
+    }
+
+    #[test]
+    fn rewind() {
+        // outputs the running total of its inputs until it reads 0
+        let data = vec![
+            109, 20, 3, 19, 1006, 19, 18, 20201, 0, 19, 0, 204, 0, 1105, 1, 2, 0, 0, 99, 0,
+        ];
+
+        let mut c = Computer::new(data);
+        assert_eq!(0, c.rewind(1));
+        c.enable_journal(None);
+        assert_eq!(Ok(vec![1, 3]), c.run(&[1, 2]));
+        let before = c.snapshot();
+        assert_eq!(Ok(vec![13]), c.run(&[10, 0]));
+        assert!(c.halted());
+
+        // back to before the second `in`, which is undone along with the rest
+        assert_eq!(Some(3), c.rewind_to_write(19));
+        assert_eq!(2, c.offset());
+        assert_eq!(vec![0], c.snapshot().input);
+        assert!(!c.halted());
+        assert_eq!(Some(5), c.rewind_to_write(19));
+        assert_eq!(before.runs, c.snapshot().runs);
+        assert_eq!(before.offset, c.offset());
+        assert_eq!(vec![10, 0], c.snapshot().input);
+        assert_eq!(None, c.rewind_to_write(100));
+
+        // rewinding undoes memory growth too
+        let len = c.journal_len();
+        assert_eq!(len, c.rewind(len + 10));
+        assert_eq!(20, c.data().len());
+        assert_eq!(0, c.offset());
+        assert_eq!(0, c.relative_offset());
+        assert_eq!(vec![1, 2, 10, 0], c.snapshot().input);
+        assert_eq!(Ok(vec![1, 3, 13]), c.run(&[]));
+    }
+
+    #[test]
+    fn bounded_journal() {
+        let mut c = Computer::new(vec![1101, 1, 1, 7, 1105, 1, 0, 0]);
+        c.enable_journal(Some(3));
+        for _ in 0..10 {
+            c.step().unwrap();
+        }
+        assert_eq!(3, c.journal_len());
+        assert_eq!(3, c.rewind(5));
+        assert_eq!(4, c.offset());
+    }
+
+    #[test]
+    fn limits() {
+        let looping = vec![1101, 1, 1, 7, 1105, 1, 0, 0];
+
+        let mut c = Computer::new(looping.clone());
+        c.set_instruction_limit(Some(5));
+        assert_eq!(
+            Err(IntcodeError::LimitExceeded {
+                limit: Limit::Instructions,
+                offset: 4
+            }),
+            c.run(&[])
+        );
+        assert_eq!(5, c.instructions());
+        assert_eq!(2, c.data()[7]);
+        c.set_instruction_limit(Some(6));
+        assert_eq!(
+            Err(IntcodeError::LimitExceeded {
+                limit: Limit::Instructions,
+                offset: 0
+            }),
+            c.run(&[])
+        );
+
+        let mut c = Computer::new(looping.clone());
+        c.set_timeout(Duration::from_millis(10));
+        match c.run(&[]) {
+            Err(IntcodeError::LimitExceeded {
+                limit: Limit::Timeout,
+                ..
+            }) => {}
+            other => panic!("expected timeout, got {:?}", other),
+        }
+
+        let token = CancelToken::new();
+        let mut c = Computer::new(looping);
+        c.set_cancel_token(Some(token.clone()));
+        let runner = std::thread::spawn(move || c.run(&[]));
+        token.cancel();
+        match runner.join().unwrap() {
+            Err(IntcodeError::LimitExceeded {
+                limit: Limit::Cancelled,
+                ..
+            }) => {}
+            other => panic!("expected cancellation, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn profile() {
+        // sets mem[15] to 3 then counts it down in a loop at 4
+        let data = vec![
+            1101, 3, 0, 15, 1001, 15, -1, 15, 1005, 15, 4, 99, 0, 0, 0, 0,
+        ];
+        let mut c = Computer::new(data);
+        assert!(c.profile().is_none());
+        c.enable_profiling();
+        assert_eq!(Ok(vec![]), c.run(&[]));
+
+        let profile = c.take_profile().unwrap();
+        assert!(c.profile().is_none());
+        assert_eq!(8, profile.instructions());
+        assert_eq!(3, profile.executions(4));
+        assert_eq!(1, profile.executions(11));
+        assert_eq!(3, profile.opcode(5));
+        assert_eq!(4, profile.opcode(1));
+        assert_eq!(6, profile.reads(15));
+        assert_eq!(4, profile.writes(15));
+        assert_eq!(0, profile.reads(3));
+        assert_eq!(
+            "L0;0:add 1\nL0;4:add 1\nL0;8:jt 1\nL4;4:add 2\nL4;8:jt 2\nL4;11:hlt 1\n",
+            profile.folded()
+        );
+        assert!(profile
+            .to_string()
+            .contains("\nadd               4  50.00\n"));
+    }
+
+    #[test]
+    fn overflow() {
+        // outputs 2^62 * 4
+        let data = vec![1102, 1 << 62, 4, 7, 4, 7, 99, 0];
+
+        let mut c = Computer::new(data.clone());
+        assert_eq!(Err(IntcodeError::Overflow { offset: 0 }), c.run(&[]));
+        assert_eq!(0, c.offset());
+
+        let mut c = Computer::new(data.clone());
+        c.set_overflow(Overflow::Wrap);
+        assert_eq!(Ok(vec![0]), c.run(&[]));
+
+        let mut c = Computer::new(data.clone());
+        c.set_overflow(Overflow::Saturate);
+        assert_eq!(Ok(vec![i64::MAX]), c.run(&[]));
+
+        let big: Vec<BigInt> = data.into_iter().map(BigInt::from).collect();
+        let mut c = Computer::with_memory(DenseMemory::from(big));
+        let output = c.run(&[]).unwrap();
+        assert_eq!("18446744073709551616", output[0].to_string());
+
+        // relative base pushed past the end of the address space
+        let mut c = Computer::new(vec![109, i64::MAX, 109, 1, 99]);
+        assert_eq!(Err(IntcodeError::Overflow { offset: 2 }), c.run(&[]));
+    }
+
+    #[test]
+    fn cell_types() {
+        // compares its input to 8, outputting 999 if below, 1000 if equal and 1001 if above
+        let data: Vec<i64> = vec![
+            3, 21, 1008, 21, 8, 20, 1005, 20, 22, 107, 8, 21, 20, 1006, 20, 31, 1106, 0, 36, 98, 0,
+            0, 1002, 21, 125, 20, 4, 20, 1105, 1, 46, 104, 999, 1105, 1, 46, 1101, 1000, 1, 20, 4,
+            20, 1105, 1, 46, 98, 99,
+        ];
+
+        let small: Vec<i32> = data.iter().map(|&v| v as i32).collect();
+        let mut c = Computer::with_memory(DenseMemory::from(small.clone()));
+        assert_eq!(Ok(vec![1000]), c.run(&[8]));
+        let mut c = Computer::with_memory(PagedMemory::from(small));
+        assert_eq!(Ok(vec![1001]), c.run(&[9]));
+
+        let wide: Vec<i128> = data.iter().map(|&v| i128::from(v)).collect();
+        let mut c = Computer::with_memory(DenseMemory::from(wide));
+        assert_eq!(Ok(vec![999]), c.run(&[7]));
+
+        // 2^30 * 4 overflows i32 but not i128
+        let mut c =
+            Computer::with_memory(DenseMemory::from(vec![1102i32, 1 << 30, 4, 7, 4, 7, 99, 0]));
+        assert_eq!(Err(IntcodeError::Overflow { offset: 0 }), c.run(&[]));
+        let mut c = Computer::with_memory(DenseMemory::from(vec![
+            1102i128,
+            1 << 62,
+            4,
+            7,
+            4,
+            7,
+            99,
+            0,
+        ]));
+        assert_eq!(Ok(vec![1 << 64]), c.run(&[]));
+    }
+}
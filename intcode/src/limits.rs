@@ -0,0 +1,59 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+// The clock is only read once per this many instructions to keep timeouts cheap
+const CLOCK_INTERVAL: u64 = 1024;
+
+// Which limit stopped a `Computer`, reported through `IntcodeError::LimitExceeded`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Limit {
+    Instructions,
+    Timeout,
+    Cancelled,
+}
+
+// Shared flag that stops every computer holding a clone of it before its next instruction
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Limits {
+    pub instructions: Option<u64>,
+    pub deadline: Option<Instant>,
+    pub cancel: Option<CancelToken>,
+}
+
+impl Limits {
+    // The limit that stops a computer which has executed `executed` instructions from running another
+    pub fn exceeded(&self, executed: u64) -> Option<Limit> {
+        if matches!(self.instructions, Some(max) if executed >= max) {
+            return Some(Limit::Instructions);
+        }
+        if matches!(&self.cancel, Some(token) if token.is_cancelled()) {
+            return Some(Limit::Cancelled);
+        }
+        match self.deadline {
+            Some(deadline)
+                if executed.is_multiple_of(CLOCK_INTERVAL) && Instant::now() >= deadline =>
+            {
+                Some(Limit::Timeout)
+            }
+            _ => None,
+        }
+    }
+}
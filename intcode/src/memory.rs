@@ -0,0 +1,223 @@
+use crate::Cell;
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+pub const PAGE_SIZE: usize = 4096;
+
+// Backing store for a `Computer`'s memory, which also picks the type of value each cell holds
+// Addresses that have never been written read as 0
+pub trait Memory {
+    type Cell: Cell;
+
+    fn get(&self, pos: usize) -> Self::Cell;
+
+    fn set(&mut self, pos: usize, val: Self::Cell);
+
+    // One past the highest address in the initial program or written since
+    fn size(&self) -> usize;
+
+    // Forgets every address at or above `size`, used to undo writes that grew memory
+    fn truncate(&mut self, size: usize);
+
+    // Number of words the store would hold after writing to `pos`, used to enforce memory limits
+    fn footprint_with(&self, pos: usize) -> usize;
+
+    // Contents of the addresses from `start` to `start + len`, stopping at `size()`
+    fn read(&self, start: usize, len: usize) -> Cow<'_, [Self::Cell]>;
+
+    // Runs of words in address order that between them hold every non-zero word,
+    // so the whole of memory can be visited without touching addresses that were never written
+    fn populated(&self) -> Vec<(usize, &[Self::Cell])>;
+}
+
+// `start..start + len` limited to `0..size`
+fn window(start: usize, len: usize, size: usize) -> std::ops::Range<usize> {
+    let end = start.saturating_add(len).min(size);
+    start.min(end)..end
+}
+
+// Contiguous memory that grows to fit the highest written address
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DenseMemory<T = i64>(Vec<T>);
+
+impl<T: Cell> From<Vec<T>> for DenseMemory<T> {
+    fn from(data: Vec<T>) -> Self {
+        Self(data)
+    }
+}
+
+impl<T: Cell> Memory for DenseMemory<T> {
+    type Cell = T;
+
+    fn get(&self, pos: usize) -> T {
+        match self.0.get(pos) {
+            Some(val) => val.clone(),
+            None => T::default(),
+        }
+    }
+
+    fn set(&mut self, pos: usize, val: T) {
+        if pos >= self.0.len() {
+            self.0.resize(pos + 1, T::default());
+        }
+        self.0[pos] = val;
+    }
+
+    fn size(&self) -> usize {
+        self.0.len()
+    }
+
+    fn truncate(&mut self, size: usize) {
+        self.0.truncate(size);
+    }
+
+    fn footprint_with(&self, pos: usize) -> usize {
+        self.0.len().max(pos.saturating_add(1))
+    }
+
+    fn read(&self, start: usize, len: usize) -> Cow<'_, [T]> {
+        Cow::Borrowed(&self.0[window(start, len, self.0.len())])
+    }
+
+    fn populated(&self) -> Vec<(usize, &[T])> {
+        vec![(0, &self.0)]
+    }
+}
+
+impl<T> DenseMemory<T> {
+    pub fn as_slice(&self) -> &[T] {
+        &self.0
+    }
+}
+
+// Memory split into `PAGE_SIZE` word pages that are only allocated once written,
+// so programs that scatter writes across huge addresses stay small
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PagedMemory<T = i64> {
+    pages: HashMap<usize, Box<[T]>>,
+    size: usize,
+}
+
+impl<T: Cell> From<Vec<T>> for PagedMemory<T> {
+    fn from(data: Vec<T>) -> Self {
+        let mut memory = Self::default();
+        for (pos, val) in data.into_iter().enumerate() {
+            memory.set(pos, val);
+        }
+        memory
+    }
+}
+
+impl<T: Cell> Memory for PagedMemory<T> {
+    type Cell = T;
+
+    fn get(&self, pos: usize) -> T {
+        match self.pages.get(&(pos / PAGE_SIZE)) {
+            Some(page) => page[pos % PAGE_SIZE].clone(),
+            None => T::default(),
+        }
+    }
+
+    fn set(&mut self, pos: usize, val: T) {
+        let page = self
+            .pages
+            .entry(pos / PAGE_SIZE)
+            .or_insert_with(|| vec![T::default(); PAGE_SIZE].into_boxed_slice());
+        page[pos % PAGE_SIZE] = val;
+        self.size = self.size.max(pos + 1);
+    }
+
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    fn truncate(&mut self, size: usize) {
+        if size >= self.size {
+            return;
+        }
+        self.pages.retain(|&page, _| page * PAGE_SIZE < size);
+        if let Some(page) = self.pages.get_mut(&(size / PAGE_SIZE)) {
+            page[size % PAGE_SIZE..].fill(T::default());
+        }
+        self.size = size;
+    }
+
+    fn footprint_with(&self, pos: usize) -> usize {
+        let pages = self.pages.len() + usize::from(!self.pages.contains_key(&(pos / PAGE_SIZE)));
+        pages * PAGE_SIZE
+    }
+
+    fn read(&self, start: usize, len: usize) -> Cow<'_, [T]> {
+        let range = window(start, len, self.size);
+        let page = range.start / PAGE_SIZE;
+        if range.end <= (page + 1) * PAGE_SIZE {
+            if let Some(words) = self.pages.get(&page) {
+                return Cow::Borrowed(
+                    &words[range.start % PAGE_SIZE..range.end - page * PAGE_SIZE],
+                );
+            }
+        }
+        Cow::Owned(range.map(|pos| self.get(pos)).collect())
+    }
+
+    fn populated(&self) -> Vec<(usize, &[T])> {
+        let mut pages: Vec<usize> = self.pages.keys().copied().collect();
+        pages.sort_unstable();
+        pages
+            .into_iter()
+            .map(|page| {
+                let start = page * PAGE_SIZE;
+                let len = PAGE_SIZE.min(self.size - start);
+                (start, &self.pages[&page][..len])
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paged() {
+        let mut m = PagedMemory::from(vec![1i64, 2, 3]);
+        assert_eq!(3, m.size());
+        assert_eq!(PAGE_SIZE, m.footprint_with(10));
+        assert_eq!(2 * PAGE_SIZE, m.footprint_with(1 << 40));
+
+        m.set(1 << 40, 7);
+        assert_eq!(7, m.get(1 << 40));
+        assert_eq!(0, m.get((1 << 40) - 1));
+        assert_eq!(2, m.get(1));
+        assert_eq!((1 << 40) + 1, m.size());
+        assert_eq!(2, m.pages.len());
+        assert_eq!(&[0, 7][..], &m.read((1 << 40) - 1, 10)[..]);
+        assert_eq!(&[2, 3, 0][..], &m.read(1, 3)[..]);
+        assert_eq!(&[0, 0][..], &m.read(PAGE_SIZE - 1, 2)[..]);
+        let populated = m.populated();
+        assert_eq!(
+            vec![0, 1 << 40],
+            populated.iter().map(|p| p.0).collect::<Vec<_>>()
+        );
+        assert_eq!((PAGE_SIZE, 1), (populated[0].1.len(), populated[1].1.len()));
+
+        m.truncate(2);
+        assert_eq!(2, m.size());
+        assert_eq!(1, m.pages.len());
+        assert_eq!(0, m.get(2));
+        assert_eq!(0, m.get(1 << 40));
+    }
+
+    #[test]
+    fn dense() {
+        let mut m = DenseMemory::from(vec![1i64, 2, 3]);
+        assert_eq!(0, m.get(10));
+        assert_eq!(11, m.footprint_with(10));
+        m.set(5, 9);
+        assert_eq!(&[1, 2, 3, 0, 0, 9][..], m.as_slice());
+        assert_eq!(&[3, 0][..], &m.read(2, 2)[..]);
+        assert_eq!(&[9][..], &m.read(5, 100)[..]);
+        assert!(m.read(100, 1).is_empty());
+        assert_eq!(vec![(0, m.as_slice())], m.populated());
+    }
+}
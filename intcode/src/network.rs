@@ -0,0 +1,348 @@
+use crate::{Computer, DenseMemory, IntcodeError, Memory, RunState};
+use std::fmt;
+
+// Error from one of the machines in a `Network`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NetworkError {
+    pub node: String,
+    pub error: IntcodeError,
+}
+
+impl fmt::Display for NetworkError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.node, self.error)
+    }
+}
+
+impl std::error::Error for NetworkError {}
+
+// State of a single machine once its network has run to quiescence
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NodeReport<T = i64> {
+    pub name: String,
+    pub outputs: Vec<T>, // every value the machine has output, in order
+    pub halted: bool,
+}
+
+struct Node<M: Memory> {
+    name: String,
+    ports: Vec<String>,
+    computer: Computer<M>,
+    outputs: Vec<M::Cell>,
+}
+
+// Destination of a node's output, either every value it outputs or only those on one port
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Edge {
+    port: Option<usize>,
+    to: usize,
+}
+
+// Directed graph of machines where outputs of a node are sent to the input of each node it's connected to
+//
+// A node's outputs are dealt to its named output ports in turn, so a machine that outputs `x, y` pairs
+// on ports `x` and `y` can send each coordinate somewhere different. Nodes added without ports have the single port `out`.
+pub struct Network<M: Memory = DenseMemory> {
+    nodes: Vec<Node<M>>,
+    edges: Vec<Vec<Edge>>, // destinations of each node's output, indexed like `nodes`
+}
+
+impl Network {
+    // Runs a copy of `program` per phase, each seeded with its phase and feeding the next
+    // Nodes are named `A`, `B`, ... and the first node's input and last node's output are left unconnected
+    pub fn chain(program: &[i64], phases: &[i64]) -> Self {
+        Self::chain_from(&Computer::new(program.to_vec()), phases)
+    }
+
+    // Like `chain` but the last node's output is fed back into the first node
+    pub fn ring(program: &[i64], phases: &[i64]) -> Self {
+        Self::ring_from(&Computer::new(program.to_vec()), phases)
+    }
+}
+
+impl<M: Memory + Clone> Network<M> {
+    // Like `chain` but each node is a clone of `template`
+    pub fn chain_from(template: &Computer<M>, phases: &[M::Cell]) -> Self {
+        let mut net = Self::new();
+        for (i, phase) in phases.iter().enumerate() {
+            let mut computer = template.clone();
+            computer.push_input(std::slice::from_ref(phase));
+            let id = net.add_node(&node_name(i), computer);
+            if i > 0 {
+                net.connect(id - 1, id);
+            }
+        }
+        net
+    }
+
+    // Like `ring` but each node is a clone of `template`
+    pub fn ring_from(template: &Computer<M>, phases: &[M::Cell]) -> Self {
+        let mut net = Self::chain_from(template, phases);
+        if !phases.is_empty() {
+            net.connect(phases.len() - 1, 0);
+        }
+        net
+    }
+}
+
+impl<M: Memory> Network<M> {
+    pub fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            edges: Vec::new(),
+        }
+    }
+
+    // Adds a machine with the single output port `out` to the network, returning its id
+    pub fn add_node(&mut self, name: &str, computer: Computer<M>) -> usize {
+        self.add_node_with_ports(name, computer, &["out"])
+    }
+
+    // Adds a machine whose outputs go to each of `ports` in turn, returning its id
+    pub fn add_node_with_ports(
+        &mut self,
+        name: &str,
+        computer: Computer<M>,
+        ports: &[&str],
+    ) -> usize {
+        assert!(!ports.is_empty(), "Node {} has no ports", name);
+        self.nodes.push(Node {
+            name: name.to_string(),
+            ports: ports.iter().map(|p| p.to_string()).collect(),
+            computer,
+            outputs: Vec::new(),
+        });
+        self.edges.push(Vec::new());
+        self.nodes.len() - 1
+    }
+
+    // Sends every future output of node `from` to the input of node `to`, whichever port it's on
+    pub fn connect(&mut self, from: usize, to: usize) {
+        self.check_node(from);
+        self.check_node(to);
+        self.edges[from].push(Edge { port: None, to });
+    }
+
+    // Sends future outputs on `port` of node `from` to the input of node `to`
+    pub fn connect_port(&mut self, from: usize, port: &str, to: usize) {
+        self.check_node(from);
+        self.check_node(to);
+        let node = &self.nodes[from];
+        let port = match node.ports.iter().position(|p| p == port) {
+            Some(port) => port,
+            None => panic!("Unknown port {} on node {}", port, node.name),
+        };
+        self.edges[from].push(Edge {
+            port: Some(port),
+            to,
+        });
+    }
+
+    fn check_node(&self, id: usize) {
+        assert!(id < self.nodes.len(), "Unknown node {}", id);
+    }
+
+    // Id of the node called `name`
+    pub fn node(&self, name: &str) -> Option<usize> {
+        self.nodes.iter().position(|n| n.name == name)
+    }
+
+    pub fn push_input(&mut self, id: usize, input: &[M::Cell]) {
+        self.nodes[id].computer.push_input(input);
+    }
+
+    pub fn computer(&self, id: usize) -> &Computer<M> {
+        &self.nodes[id].computer
+    }
+
+    // Runs every machine in turn until each one has either halted or is awaiting input nobody has sent
+    pub fn run(&mut self) -> Result<Vec<NodeReport<M::Cell>>, NetworkError> {
+        loop {
+            for i in 0..self.nodes.len() {
+                if self.nodes[i].computer.halted() {
+                    continue;
+                }
+                loop {
+                    let node = &mut self.nodes[i];
+                    match node.computer.step_until_event() {
+                        Ok(RunState::Output(val)) => {
+                            let port = node.outputs.len() % node.ports.len();
+                            node.outputs.push(val.clone());
+                            for edge in &self.edges[i] {
+                                if edge.port.is_none_or(|p| p == port) {
+                                    self.nodes[edge.to]
+                                        .computer
+                                        .push_input(std::slice::from_ref(&val));
+                                }
+                            }
+                        }
+                        Ok(RunState::AwaitingInput) | Ok(RunState::Halted) => break,
+                        Err(error) => {
+                            return Err(NetworkError {
+                                node: node.name.clone(),
+                                error,
+                            })
+                        }
+                    }
+                }
+            }
+
+            if self
+                .nodes
+                .iter()
+                .all(|n| n.computer.halted() || n.computer.pending_input() == 0)
+            {
+                break;
+            }
+        }
+
+        Ok(self.report())
+    }
+
+    pub fn report(&self) -> Vec<NodeReport<M::Cell>> {
+        self.nodes
+            .iter()
+            .map(|n| NodeReport {
+                name: n.name.clone(),
+                outputs: n.outputs.clone(),
+                halted: n.computer.halted(),
+            })
+            .collect()
+    }
+}
+
+impl<M: Memory> Default for Network<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn node_name(i: usize) -> String {
+    if i < 26 {
+        char::from(b'A' + i as u8).to_string()
+    } else {
+        i.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chain() {
+        let data = vec![
+            3, 15, 3, 16, 1002, 16, 10, 16, 1, 16, 15, 15, 4, 15, 99, 0, 0,
+        ];
+
+        let mut net = Network::chain(&data, &[4, 3, 2, 1, 0]);
+        net.push_input(0, &[0]);
+        let report = net.run().unwrap();
+        assert_eq!(5, report.len());
+        assert_eq!("E", report[4].name);
+        assert_eq!(vec![43210], report[4].outputs);
+        assert!(report.iter().all(|n| n.halted));
+    }
+
+    #[test]
+    fn ring() {
+        let data = vec![
+            3, 26, 1001, 26, -4, 26, 3, 27, 1002, 27, 2, 27, 1, 27, 26, 27, 4, 27, 1001, 28, -1,
+            28, 1005, 28, 6, 99, 0, 0, 5,
+        ];
+
+        let mut net = Network::ring(&data, &[9, 8, 7, 6, 5]);
+        net.push_input(net.node("A").unwrap(), &[0]);
+        let report = net.run().unwrap();
+        assert_eq!(Some(&139_629_729), report[4].outputs.last());
+        assert!(report.iter().all(|n| n.halted));
+    }
+
+    #[test]
+    fn graph() {
+        // echoes every input forever
+        let echo = vec![3, 100, 4, 100, 1105, 1, 0];
+        // outputs the sum of two inputs then halts
+        let sum = vec![3, 100, 3, 101, 1, 100, 101, 102, 4, 102, 99];
+
+        let mut net = Network::new();
+        let src = net.add_node("src", Computer::new_with_input(echo.clone(), &[3]));
+        let left = net.add_node("left", Computer::new(echo.clone()));
+        let right = net.add_node("right", Computer::new(echo));
+        let total = net.add_node("total", Computer::new(sum));
+        net.connect(src, left);
+        net.connect(src, right);
+        net.connect(left, total);
+        net.connect(right, total);
+
+        let report = net.run().unwrap();
+        assert_eq!(vec![6], report[total].outputs);
+        assert!(report[total].halted);
+        assert!(!report[src].halted);
+    }
+
+    #[test]
+    fn ports() {
+        // outputs each input then ten times it
+        let split = vec![3, 100, 4, 100, 1002, 100, 10, 100, 4, 100, 1105, 1, 0];
+        // sums inputs until it reads 0, then outputs the total
+        let sum = vec![
+            3, 100, 1006, 100, 13, 1, 100, 101, 101, 1105, 1, 0, 99, 4, 101, 99,
+        ];
+
+        let mut net = Network::new();
+        let src = net.add_node_with_ports(
+            "split",
+            Computer::new_with_input(split, &[1, 2, 3]),
+            &["ones", "tens"],
+        );
+        let ones = net.add_node("ones", Computer::new(sum.clone()));
+        let tens = net.add_node("tens", Computer::new(sum.clone()));
+        let all = net.add_node("all", Computer::new(sum));
+        net.connect_port(src, "ones", ones);
+        net.connect_port(src, "tens", tens);
+        net.connect(src, all);
+
+        let report = net.run().unwrap();
+        assert_eq!(vec![1, 10, 2, 20, 3, 30], report[src].outputs);
+        assert!(report[1..]
+            .iter()
+            .all(|n| !n.halted && n.outputs.is_empty()));
+
+        net.push_input(src, &[0]);
+        let report = net.run().unwrap();
+        assert_eq!(vec![6], report[ones].outputs);
+        assert_eq!(vec![60], report[tens].outputs);
+        assert_eq!(vec![66], report[all].outputs);
+    }
+
+    #[test]
+    #[should_panic(expected = "Unknown node 3")]
+    fn unknown_node() {
+        let mut net = Network::chain(&[99], &[1, 2]);
+        net.connect(3, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Unknown port x on node A")]
+    fn unknown_port() {
+        let mut net = Network::chain(&[99], &[1, 2]);
+        net.connect_port(0, "x", 1);
+    }
+
+    #[test]
+    fn error() {
+        let mut net = Network::chain(&[3, 0, 4, 0, 42], &[1, 2]);
+        assert_eq!(
+            Err(NetworkError {
+                node: "A".to_string(),
+                error: IntcodeError::UnknownOpcode {
+                    opcode: 42,
+                    offset: 4
+                }
+            }),
+            net.run()
+        );
+    }
+}
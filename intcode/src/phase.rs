@@ -0,0 +1,214 @@
+use crate::network::{Network, NetworkError};
+use crate::pool::{self, Queue};
+use crate::{Computer, Memory};
+
+// Lazily yields every ordering of every `k` element subset of `items`
+// Subsets are visited in lexicographic order of position and each is permuted with Heap's algorithm
+pub struct Permutations<T: Clone> {
+    items: Vec<T>,
+    combo: Vec<usize>, // positions in `items` of the current subset
+    current: Vec<T>,   // current ordering of the subset
+    c: Vec<usize>,     // Heap's algorithm stack state
+    i: usize,          // Heap's algorithm index
+    fresh: bool,       // `current` hasn't been yielded yet
+    done: bool,
+}
+
+impl<T: Clone> Permutations<T> {
+    pub fn new(items: &[T], k: usize) -> Self {
+        let combo: Vec<usize> = (0..k).collect();
+        let done = k > items.len();
+        let current = if done {
+            Vec::new()
+        } else {
+            combo.iter().map(|&i| items[i].clone()).collect()
+        };
+        Self {
+            items: items.to_vec(),
+            combo,
+            current,
+            c: vec![0; k],
+            i: 1,
+            fresh: true,
+            done,
+        }
+    }
+
+    // Moves to the next subset, returning false once every subset has been visited
+    fn next_combo(&mut self) -> bool {
+        let n = self.items.len();
+        let k = self.combo.len();
+        let j = match (0..k).rev().find(|&j| self.combo[j] < n - k + j) {
+            Some(j) => j,
+            None => return false,
+        };
+        self.combo[j] += 1;
+        for l in j + 1..k {
+            self.combo[l] = self.combo[l - 1] + 1;
+        }
+        self.current = self.combo.iter().map(|&i| self.items[i].clone()).collect();
+        self.c.fill(0);
+        self.i = 1;
+        true
+    }
+}
+
+impl<T: Clone> Iterator for Permutations<T> {
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Vec<T>> {
+        if self.done {
+            return None;
+        }
+        if self.fresh {
+            self.fresh = false;
+            return Some(self.current.clone());
+        }
+        while self.i < self.current.len() {
+            let i = self.i;
+            if self.c[i] < i {
+                if i.is_multiple_of(2) {
+                    self.current.swap(0, i);
+                } else {
+                    self.current.swap(self.c[i], i);
+                }
+                self.c[i] += 1;
+                self.i = 1;
+                return Some(self.current.clone());
+            }
+            self.c[i] = 0;
+            self.i += 1;
+        }
+        if self.next_combo() {
+            Some(self.current.clone())
+        } else {
+            self.done = true;
+            None
+        }
+    }
+}
+
+// Phases with the highest signal seen so far and that signal
+type Best<T> = Option<(Vec<T>, T)>;
+
+// Tries every ordering of `amplifiers` distinct phases from `alphabet` on a chain of clones of `template`,
+// or a ring if `feedback` is set, and returns the phases that produce the highest final signal with that signal
+// The first amplifier is given an initial signal of 0, candidates are spread across all available cores,
+// and ties go to the lexicographically smallest phases
+pub fn best_phases<M: Memory + Clone + Send>(
+    template: &Computer<M>,
+    alphabet: &[M::Cell],
+    amplifiers: usize,
+    feedback: bool,
+) -> Result<Best<M::Cell>, NetworkError> {
+    let results = pool::run_workers(
+        Permutations::new(alphabet, amplifiers),
+        || template.clone(),
+        |template, candidates| evaluate(&template, candidates, feedback),
+    );
+
+    let mut best: Best<M::Cell> = None;
+    for result in results {
+        if let Some((phases, signal)) = result? {
+            best = match best {
+                Some((best_phases, best_signal))
+                    if best_signal > signal || (best_signal == signal && best_phases < phases) =>
+                {
+                    Some((best_phases, best_signal))
+                }
+                _ => Some((phases, signal)),
+            };
+        }
+    }
+    Ok(best)
+}
+
+// Pulls candidates until none are left, returning the best this worker saw
+fn evaluate<M: Memory + Clone>(
+    template: &Computer<M>,
+    candidates: &Queue<Permutations<M::Cell>>,
+    feedback: bool,
+) -> Result<Best<M::Cell>, NetworkError> {
+    let mut best: Best<M::Cell> = None;
+    loop {
+        let phases = match candidates.next() {
+            Some(p) => p,
+            None => return Ok(best),
+        };
+
+        let mut net = if feedback {
+            Network::ring_from(template, &phases)
+        } else {
+            Network::chain_from(template, &phases)
+        };
+        if !phases.is_empty() {
+            net.push_input(0, &[M::Cell::default()]);
+        }
+        let report = net.run()?;
+        let signal = match report.last().and_then(|amp| amp.outputs.last()) {
+            Some(signal) => signal.clone(),
+            None => continue,
+        };
+
+        let better = match &best {
+            Some((best_phases, best_signal)) => {
+                signal > *best_signal || (signal == *best_signal && phases < *best_phases)
+            }
+            None => true,
+        };
+        if better {
+            best = Some((phases, signal));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn permutations() {
+        let all: Vec<_> = Permutations::new(&[1, 2, 3, 4, 5], 5).collect();
+        assert_eq!(120, all.len());
+        assert_eq!(120, all.iter().collect::<HashSet<_>>().len());
+
+        let partial: Vec<_> = Permutations::new(&[1, 2, 3, 4], 2).collect();
+        assert_eq!(12, partial.len());
+        assert_eq!(12, partial.iter().collect::<HashSet<_>>().len());
+        assert!(partial.iter().all(|p| p.len() == 2 && p[0] != p[1]));
+
+        assert_eq!(
+            vec![Vec::<i64>::new()],
+            Permutations::new(&[1, 2], 0).collect::<Vec<_>>()
+        );
+        assert_eq!(0, Permutations::new(&[1, 2], 3).count());
+    }
+
+    #[test]
+    fn chain() {
+        let data = vec![
+            3, 23, 3, 24, 1002, 24, 10, 24, 1002, 23, -1, 23, 101, 5, 23, 23, 1, 24, 23, 23, 4, 23,
+            99, 0, 0,
+        ];
+
+        assert_eq!(
+            Ok(Some((vec![0, 1, 2, 3, 4], 54321))),
+            best_phases(&Computer::new(data), &[0, 1, 2, 3, 4], 5, false)
+        );
+    }
+
+    #[test]
+    fn feedback() {
+        let data = vec![
+            3, 52, 1001, 52, -5, 52, 3, 53, 1, 52, 56, 54, 1007, 54, 5, 55, 1005, 55, 26, 1001, 54,
+            -5, 54, 1105, 1, 12, 1, 53, 54, 53, 1008, 54, 0, 55, 1001, 55, 1, 55, 2, 53, 55, 53, 4,
+            53, 1001, 56, -1, 56, 1005, 56, 6, 99, 0, 0, 0, 0, 10,
+        ];
+
+        assert_eq!(
+            Ok(Some((vec![9, 7, 8, 5, 6], 18216))),
+            best_phases(&Computer::new(data), &[5, 6, 7, 8, 9], 5, true)
+        );
+    }
+}
@@ -0,0 +1,46 @@
+use std::sync::Mutex;
+use std::thread;
+
+// Items shared between the workers started by `run_workers`, handed out in order
+pub(crate) struct Queue<I>(Mutex<I>);
+
+impl<I: Iterator> Queue<I> {
+    // Next item, or `None` once they run out or another worker has panicked and will report it
+    pub fn next(&self) -> Option<I::Item> {
+        match self.0.lock() {
+            Ok(mut items) => items.next(),
+            Err(_) => None,
+        }
+    }
+}
+
+// Runs a worker per available core, each given its own state from `init` and pulling from the shared queue of `items`,
+// and returns what every worker returned once they all have
+// A panic in any worker is resumed on the calling thread
+pub(crate) fn run_workers<I, S, R, F>(items: I, mut init: impl FnMut() -> S, work: F) -> Vec<R>
+where
+    I: Iterator + Send,
+    S: Send,
+    R: Send,
+    F: Fn(S, &Queue<I>) -> R + Sync,
+{
+    let threads = thread::available_parallelism().map_or(1, usize::from);
+    let queue = Queue(Mutex::new(items));
+
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|_| {
+                let state = init();
+                let (queue, work) = (&queue, &work);
+                scope.spawn(move || work(state, queue))
+            })
+            .collect();
+        workers
+            .into_iter()
+            .map(|w| match w.join() {
+                Ok(result) => result,
+                Err(e) => std::panic::resume_unwind(e),
+            })
+            .collect()
+    })
+}
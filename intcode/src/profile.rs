@@ -0,0 +1,157 @@
+use crate::OPCODES;
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+
+// Rows shown per table by `Profile`'s `Display`
+const DEFAULT_TOP: usize = 20;
+
+// Execution counts gathered while profiling is enabled with `Computer::enable_profiling`
+// Memory reads only count parameters read through an address (position and relative mode),
+// not instruction words or immediate parameters
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Profile {
+    instructions: u64,
+    executions: HashMap<usize, u64>,
+    opcodes: HashMap<i64, u64>,
+    reads: HashMap<usize, u64>,
+    writes: HashMap<usize, u64>,
+    blocks: HashMap<(usize, usize, i64), u64>, // (block start, offset, opcode)
+    block: usize,                              // where execution last arrived by a jump
+}
+
+impl Profile {
+    pub(crate) fn new(offset: usize) -> Self {
+        Self {
+            block: offset,
+            ..Self::default()
+        }
+    }
+
+    pub(crate) fn read(&mut self, pos: usize) {
+        *self.reads.entry(pos).or_insert(0) += 1;
+    }
+
+    pub(crate) fn write(&mut self, pos: usize) {
+        *self.writes.entry(pos).or_insert(0) += 1;
+    }
+
+    // Records the instruction at `offset`, which moved the instruction pointer to `next`
+    pub(crate) fn executed(&mut self, offset: usize, opcode: i64, next: usize) {
+        self.instructions += 1;
+        *self.executions.entry(offset).or_insert(0) += 1;
+        *self.opcodes.entry(opcode).or_insert(0) += 1;
+        *self.blocks.entry((self.block, offset, opcode)).or_insert(0) += 1;
+        if (opcode == 5 || opcode == 6) && next != offset + 3 {
+            self.block = next;
+        }
+    }
+
+    pub fn instructions(&self) -> u64 {
+        self.instructions
+    }
+
+    // Times the instruction at `offset` was executed
+    pub fn executions(&self, offset: usize) -> u64 {
+        self.executions.get(&offset).copied().unwrap_or(0)
+    }
+
+    pub fn opcode(&self, opcode: i64) -> u64 {
+        self.opcodes.get(&opcode).copied().unwrap_or(0)
+    }
+
+    pub fn reads(&self, pos: usize) -> u64 {
+        self.reads.get(&pos).copied().unwrap_or(0)
+    }
+
+    pub fn writes(&self, pos: usize) -> u64 {
+        self.writes.get(&pos).copied().unwrap_or(0)
+    }
+
+    // Folded stacks for flamegraph tools, one `L<block>;<offset>:<mnemonic> <count>` line per instruction,
+    // where the block is the jump target execution last arrived at (or where profiling started)
+    pub fn folded(&self) -> String {
+        let mut lines: Vec<_> = self.blocks.iter().collect();
+        lines.sort();
+        let mut out = String::new();
+        for (&(block, offset, opcode), count) in lines {
+            writeln!(out, "L{};{}:{} {}", block, offset, mnemonic(opcode), count).unwrap();
+        }
+        out
+    }
+
+    // Tables of instructions by opcode, the `top` most executed addresses and the `top` busiest memory cells
+    pub fn report(&self, top: usize) -> String {
+        let mut out = String::new();
+        writeln!(out, "{} instructions executed", self.instructions).unwrap();
+
+        writeln!(out, "\nopcode        count      %").unwrap();
+        for (opcode, count) in sorted(&self.opcodes, usize::MAX) {
+            writeln!(
+                out,
+                "{:<6} {:>12} {:>6.2}",
+                mnemonic(opcode),
+                count,
+                self.percent(count)
+            )
+            .unwrap();
+        }
+
+        writeln!(out, "\naddress       count      %").unwrap();
+        for (offset, count) in sorted(&self.executions, top) {
+            writeln!(
+                out,
+                "{:>7} {:>11} {:>6.2}",
+                offset,
+                count,
+                self.percent(count)
+            )
+            .unwrap();
+        }
+
+        let mut cells: HashMap<usize, u64> = self.reads.clone();
+        for (&pos, &count) in &self.writes {
+            *cells.entry(pos).or_insert(0) += count;
+        }
+        writeln!(out, "\ncell           reads       writes").unwrap();
+        for (pos, _) in sorted(&cells, top) {
+            writeln!(
+                out,
+                "{:>7} {:>12} {:>12}",
+                pos,
+                self.reads(pos),
+                self.writes(pos)
+            )
+            .unwrap();
+        }
+        out
+    }
+
+    fn percent(&self, count: u64) -> f64 {
+        if self.instructions == 0 {
+            0.0
+        } else {
+            count as f64 * 100.0 / self.instructions as f64
+        }
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.report(DEFAULT_TOP))
+    }
+}
+
+// Up to `top` entries of `counts`, highest count first with ties in key order
+fn sorted<K: Copy + Ord>(counts: &HashMap<K, u64>, top: usize) -> Vec<(K, u64)> {
+    let mut entries: Vec<(K, u64)> = counts.iter().map(|(&k, &c)| (k, c)).collect();
+    entries.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    entries.truncate(top);
+    entries
+}
+
+fn mnemonic(opcode: i64) -> &'static str {
+    match OPCODES.iter().find(|op| op.0 == opcode) {
+        Some(op) => op.1,
+        None => "???",
+    }
+}
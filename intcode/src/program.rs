@@ -0,0 +1,178 @@
+// Loading Intcode programs from text or a compact binary encoding
+//
+// Text is integers separated by commas and/or whitespace over any number of lines. `;` or `#` starts a
+// comment running to the end of the line, and a comma at the end of a line is allowed.
+//
+// Binary is `ICP1` then varints: the number of words followed by each word zigzag encoded,
+// so most programs take one or two bytes per word.
+
+use crate::varint;
+use std::fmt;
+use std::io::{self, prelude::*};
+use std::path::Path;
+
+const MAGIC: &[u8; 4] = b"ICP1";
+
+// Error from loading a program
+#[derive(Debug)]
+pub enum LoadError {
+    Io(io::Error),
+    // `token` would have been word `index` of the program, found on 1-based `line`
+    InvalidToken {
+        index: usize,
+        line: usize,
+        token: String,
+    },
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{}", e),
+            Self::InvalidToken { index, line, token } => write!(
+                f,
+                "Invalid token {:?} at index {} on line {}",
+                token, index, line
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::InvalidToken { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for LoadError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+// Parses a program from text
+pub fn parse(text: &str) -> Result<Vec<i64>, LoadError> {
+    let mut program = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let code = match line.find([';', '#']) {
+            Some(start) => &line[..start],
+            None => line,
+        };
+        let fields: Vec<&str> = code.split(',').collect();
+        for (j, field) in fields.iter().enumerate() {
+            // only the field after a trailing comma may be empty
+            if field.trim().is_empty() && j + 1 < fields.len() {
+                return Err(LoadError::InvalidToken {
+                    index: program.len(),
+                    line: i + 1,
+                    token: String::new(),
+                });
+            }
+            for token in field.split_whitespace() {
+                match token.parse::<i64>() {
+                    Ok(val) => program.push(val),
+                    Err(_) => {
+                        return Err(LoadError::InvalidToken {
+                            index: program.len(),
+                            line: i + 1,
+                            token: token.to_string(),
+                        })
+                    }
+                }
+            }
+        }
+    }
+    Ok(program)
+}
+
+pub fn write_binary<W: Write + ?Sized>(w: &mut W, program: &[i64]) -> io::Result<()> {
+    w.write_all(MAGIC)?;
+    varint::write_u64(w, program.len() as u64)?;
+    for &val in program {
+        varint::write_i64(w, val)?;
+    }
+    Ok(())
+}
+
+pub fn read_binary<R: Read + ?Sized>(r: &mut R) -> io::Result<Vec<i64>> {
+    let mut magic = [0; 4];
+    r.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "not a binary Intcode program",
+        ));
+    }
+    let len = varint::expect_u64(r)?;
+    let mut program = Vec::new();
+    for _ in 0..len {
+        program.push(varint::expect_i64(r)?);
+    }
+    Ok(program)
+}
+
+// Loads a program from `path`, reading it as binary if it starts with the binary header and as text otherwise
+pub fn load<P: AsRef<Path>>(path: P) -> Result<Vec<i64>, LoadError> {
+    let bytes = std::fs::read(path)?;
+    if bytes.starts_with(MAGIC) {
+        return Ok(read_binary(&mut &bytes[..])?);
+    }
+    match std::str::from_utf8(&bytes) {
+        Ok(text) => parse(text),
+        Err(e) => Err(io::Error::new(io::ErrorKind::InvalidData, e).into()),
+    }
+}
+
+pub fn save_binary<P: AsRef<Path>>(path: P, program: &[i64]) -> io::Result<()> {
+    let mut w = io::BufWriter::new(std::fs::File::create(path)?);
+    write_binary(&mut w, program)?;
+    w.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invalid(result: Result<Vec<i64>, LoadError>) -> (usize, usize, String) {
+        match result {
+            Err(LoadError::InvalidToken { index, line, token }) => (index, line, token),
+            other => panic!("Expected invalid token, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn text() {
+        assert_eq!(vec![1, 0, 0, 3, 99], parse("1,0,0,3,99\n").unwrap());
+        assert_eq!(vec![1, -2, 3], parse("1, -2,\n3\n\n").unwrap());
+        assert_eq!(vec![1, 2, 3, 4], parse("1 2\t3\r\n 4").unwrap());
+        assert_eq!(
+            vec![1101, 2, 3, 0, 99],
+            parse("; add\n1101, 2, 3, 0, # sum to 0\n99 ; halt\n").unwrap()
+        );
+        assert_eq!(Vec::<i64>::new(), parse("\n# nothing\n").unwrap());
+
+        assert_eq!((2, 1, "x".to_string()), invalid(parse("1,2,x,4")));
+        assert_eq!((3, 2, "4.5".to_string()), invalid(parse("1,2\n3 4.5")));
+        assert_eq!((1, 1, String::new()), invalid(parse("1,,2")));
+        assert_eq!((0, 1, String::new()), invalid(parse(",1")));
+        assert_eq!(
+            "Invalid token \"99999999999999999999\" at index 0 on line 1",
+            parse("99999999999999999999").unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn binary() {
+        let program = vec![109, 1, 204, -1, 1001, 100, 1, 100, i64::MIN, i64::MAX, 0];
+        let mut buf = Vec::new();
+        write_binary(&mut buf, &program).unwrap();
+        assert_eq!(&buf[..6], b"ICP1\x0b\xda");
+        assert_eq!(program, read_binary(&mut &buf[..]).unwrap());
+
+        assert!(read_binary(&mut &buf[..buf.len() - 1]).is_err());
+        assert!(read_binary(&mut &b"1,2,3"[..]).is_err());
+    }
+}
@@ -0,0 +1,322 @@
+// Property tests checking `Computer` against a plain reference model of the instruction set
+// on randomly generated operands and modes
+use crate::fuzz::Rng;
+use crate::{Computer, IntcodeError, RunState};
+
+const CASES: usize = 500;
+// Small enough to compare whole memories, large enough for writes well past the end of every program
+const MEMORY_LIMIT: usize = 256;
+
+// Straightforward interpreter written from the puzzle text rather than from `Computer`
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Model {
+    memory: Vec<i64>,
+    offset: usize,
+    relative_offset: i64,
+    halted: bool,
+    input: Vec<i64>,
+}
+
+impl Model {
+    fn new(memory: Vec<i64>, input: &[i64]) -> Self {
+        Self {
+            memory,
+            offset: 0,
+            relative_offset: 0,
+            halted: false,
+            input: input.to_vec(),
+        }
+    }
+
+    fn get(&self, pos: usize) -> i64 {
+        self.memory.get(pos).copied().unwrap_or(0)
+    }
+
+    fn address(&self, pos: i64, base: i64) -> Result<usize, IntcodeError> {
+        let offset = self.offset;
+        let address = pos
+            .checked_add(base)
+            .ok_or(IntcodeError::Overflow { offset })?;
+        if address < 0 {
+            return Err(IntcodeError::NegativeAddress { address, offset });
+        }
+        Ok(address as usize)
+    }
+
+    fn param(&self, i: usize, mode: i64) -> Result<i64, IntcodeError> {
+        let raw = self.get(self.offset + 1 + i);
+        match mode {
+            0 => Ok(self.get(self.address(raw, 0)?)),
+            1 => Ok(raw),
+            2 => Ok(self.get(self.address(raw, self.relative_offset)?)),
+            _ => Err(IntcodeError::InvalidMode {
+                mode,
+                offset: self.offset,
+            }),
+        }
+    }
+
+    fn write(&mut self, i: usize, mode: i64, val: i64) -> Result<(), IntcodeError> {
+        let offset = self.offset;
+        let raw = self.get(offset + 1 + i);
+        let address = match mode {
+            0 => self.address(raw, 0)?,
+            1 => return Err(IntcodeError::ImmediateWrite { offset }),
+            2 => self.address(raw, self.relative_offset)?,
+            _ => return Err(IntcodeError::InvalidMode { mode, offset }),
+        };
+        if address >= self.memory.len() {
+            if address >= MEMORY_LIMIT {
+                return Err(IntcodeError::MemoryLimit { address, offset });
+            }
+            self.memory.resize(address + 1, 0);
+        }
+        self.memory[address] = val;
+        Ok(())
+    }
+
+    fn step(&mut self) -> Result<Option<RunState>, IntcodeError> {
+        let offset = self.offset;
+        let word = self.get(offset);
+        let unknown = IntcodeError::UnknownOpcode {
+            opcode: word,
+            offset,
+        };
+        if !(0..100_000).contains(&word) {
+            return Err(unknown);
+        }
+        let modes = [word / 100 % 10, word / 1000 % 10, word / 10_000 % 10];
+
+        let (next, state) = match word % 100 {
+            opcode @ 1 | opcode @ 2 | opcode @ 7 | opcode @ 8 => {
+                let a = self.param(0, modes[0])?;
+                let b = self.param(1, modes[1])?;
+                let val = match opcode {
+                    1 => a.checked_add(b).ok_or(IntcodeError::Overflow { offset })?,
+                    2 => a.checked_mul(b).ok_or(IntcodeError::Overflow { offset })?,
+                    7 => i64::from(a < b),
+                    _ => i64::from(a == b),
+                };
+                self.write(2, modes[2], val)?;
+                (offset + 4, None)
+            }
+            3 => {
+                if self.input.is_empty() {
+                    return Ok(Some(RunState::AwaitingInput));
+                }
+                self.write(0, modes[0], self.input[0])?;
+                self.input.remove(0);
+                (offset + 2, None)
+            }
+            4 => (offset + 2, Some(RunState::Output(self.param(0, modes[0])?))),
+            opcode @ 5 | opcode @ 6 => {
+                let cond = self.param(0, modes[0])?;
+                let target = self.param(1, modes[1])?;
+                if (cond != 0) == (opcode == 5) {
+                    (self.address(target, 0)?, None)
+                } else {
+                    (offset + 3, None)
+                }
+            }
+            9 => {
+                let val = self.param(0, modes[0])?;
+                self.relative_offset = self
+                    .relative_offset
+                    .checked_add(val)
+                    .ok_or(IntcodeError::Overflow { offset })?;
+                (offset + 2, None)
+            }
+            99 => {
+                self.halted = true;
+                (offset, Some(RunState::Halted))
+            }
+            _ => return Err(unknown),
+        };
+        self.offset = next;
+        Ok(state)
+    }
+}
+
+fn computer(memory: &[i64], input: &[i64]) -> Computer {
+    let mut c = Computer::new_with_input(memory.to_vec(), input);
+    c.set_memory_limit(Some(MEMORY_LIMIT));
+    c
+}
+
+// Steps both until one stops, asserting they agree on every result and the state after it
+fn assert_matches(memory: &[i64], input: &[i64], steps: usize) {
+    let mut c = computer(memory, input);
+    let mut model = Model::new(memory.to_vec(), input);
+    for step in 0..steps {
+        let context = format!("step {} of {:?} with input {:?}", step, memory, input);
+        let expected = model.step();
+        assert_eq!(expected, c.step(), "{}", context);
+        assert_eq!(model.memory, c.data().to_vec(), "{}", context);
+        assert_eq!(model.offset, c.offset(), "{}", context);
+        assert_eq!(model.relative_offset, c.relative_offset(), "{}", context);
+        assert_eq!(model.halted, c.halted(), "{}", context);
+        assert_eq!(model.input.len(), c.pending_input(), "{}", context);
+        match expected {
+            Ok(Some(RunState::Output(_))) | Ok(None) => {}
+            _ => break,
+        }
+    }
+}
+
+// Mostly valid modes with the odd invalid one
+fn mode(rng: &mut Rng) -> i64 {
+    if rng.chance(5) {
+        rng.range(3..10)
+    } else {
+        rng.range(0..3)
+    }
+}
+
+// Operand biased towards interesting addresses and values for a program of `len` words
+fn operand(rng: &mut Rng, len: usize) -> i64 {
+    let len = len as i64;
+    match rng.below(8) {
+        0 | 1 => rng.range(0..len),    // inside the program
+        2 => rng.range(len..len + 40), // past the end
+        3 => rng.range(MEMORY_LIMIT as i64 - 2..MEMORY_LIMIT as i64 + 2), // around the memory limit
+        4 => rng.range(-5..0),         // negative
+        5 => *rng.pick(&[i64::MAX, i64::MIN, i64::MAX / 2 + 1, -1]), // overflows
+        _ => rng.range(-100..100),
+    }
+}
+
+// `arb` to a random relative base followed by a single random instruction with `opcode` and some data words
+fn single(rng: &mut Rng, opcode: i64) -> Vec<i64> {
+    let count = crate::OPCODES.iter().find(|op| op.0 == opcode).unwrap().2;
+    let len = 2 + 1 + count + rng.below(8);
+    let mut memory = vec![109, 0];
+    memory[1] = if rng.chance(50) {
+        rng.range(-10..10)
+    } else {
+        operand(rng, len)
+    };
+    memory.push(opcode + mode(rng) * 100 + mode(rng) * 1000 + mode(rng) * 10_000);
+    while memory.len() < len {
+        memory.push(operand(rng, len));
+    }
+    memory
+}
+
+#[test]
+fn each_opcode_matches_model() {
+    let mut rng = Rng::new(24);
+    for &(opcode, ..) in &crate::OPCODES {
+        for _ in 0..CASES {
+            let memory = single(&mut rng, opcode);
+            let input: Vec<i64> = (0..rng.below(2)).map(|_| rng.range(-100..100)).collect();
+            assert_matches(&memory, &input, 2);
+        }
+    }
+}
+
+#[test]
+fn random_programs_match_model() {
+    let mut rng = Rng::new(25);
+    for _ in 0..CASES {
+        let len = 1 + rng.below(40);
+        let mut memory = Vec::new();
+        while memory.len() < len {
+            if rng.chance(70) {
+                let &(opcode, _, count, _) = rng.pick(&crate::OPCODES);
+                memory.push(
+                    opcode + mode(&mut rng) * 100 + mode(&mut rng) * 1000 + mode(&mut rng) * 10_000,
+                );
+                memory.extend((0..count).map(|_| operand(&mut rng, len)));
+            } else {
+                memory.push(operand(&mut rng, len));
+            }
+        }
+        let input: Vec<i64> = (0..rng.below(4)).map(|_| rng.range(-100..100)).collect();
+        assert_matches(&memory, &input, 100);
+    }
+}
+
+#[test]
+fn writes_past_end_grow_memory() {
+    let mut rng = Rng::new(26);
+    for _ in 0..CASES {
+        let relative = rng.chance(50);
+        let rb = rng.range(-20..20);
+        let address = rng.range(8..MEMORY_LIMIT as i64);
+        let val = rng.range(-100..100);
+        let memory = if relative {
+            vec![109, rb, 21101, val, 0, address - rb, 99]
+        } else {
+            vec![109, rb, 1101, val, 0, address, 99]
+        };
+        let mut c = computer(&memory, &[]);
+        assert_eq!(Ok(vec![]), c.run(&[]));
+        let data = c.data();
+        assert_eq!(address as usize + 1, data.len());
+        assert_eq!(val, data[address as usize]);
+        assert!(data[memory.len()..address as usize].iter().all(|&w| w == 0));
+        assert_matches(&memory, &[], 3);
+    }
+}
+
+#[test]
+fn relative_writes_use_current_base() {
+    let mut rng = Rng::new(27);
+    for _ in 0..CASES {
+        // moves the base twice then stores input relative to it
+        let (first, second) = (rng.range(0..30), rng.range(-30..30));
+        let mut address = rng.range(-10..60);
+        if (0..7).contains(&address) {
+            address += 7; // keeps clear of the program itself
+        }
+        let val = rng.range(-100..100);
+        let memory = vec![109, first, 109, second, 203, address - first - second, 99];
+
+        let mut c = computer(&memory, &[val]);
+        let result = c.run(&[]);
+        if address < 0 {
+            assert_eq!(
+                Err(IntcodeError::NegativeAddress { address, offset: 4 }),
+                result
+            );
+        } else {
+            assert_eq!(Ok(vec![]), result);
+            assert_eq!(val, c.data()[address as usize]);
+        }
+        assert_matches(&memory, &[val], 4);
+    }
+}
+
+#[test]
+fn jump_to_self() {
+    let mut rng = Rng::new(28);
+    for _ in 0..CASES {
+        // pads with data so the jump lands somewhere other than 0, then jumps to itself forever
+        let pc = rng.below(20);
+        let mut memory: Vec<i64> = vec![1105, 1, pc as i64 + 3];
+        memory.extend((0..pc).map(|_| rng.range(-100..100)));
+        let cond = rng.range(-3..3);
+        let (opcode, taken) = if rng.chance(50) {
+            (1105, cond != 0)
+        } else {
+            (1106, cond == 0)
+        };
+        memory.extend(&[opcode, cond, pc as i64 + 3, 99]);
+
+        let mut c = computer(&memory, &[]);
+        c.step().unwrap();
+        let steps = if taken { 1 + rng.below(50) } else { 1 };
+        for _ in 0..steps {
+            assert_eq!(Ok(None), c.step());
+        }
+        if taken {
+            assert_eq!(pc + 3, c.offset());
+            assert!(!c.halted());
+        } else {
+            assert_eq!(pc + 6, c.offset());
+        }
+        assert_eq!(memory, c.data().to_vec());
+        assert_eq!(1 + steps as u64, c.instructions());
+        assert_matches(&memory, &[], steps + 2);
+    }
+}
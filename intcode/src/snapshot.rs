@@ -0,0 +1,225 @@
+use crate::varint;
+use std::io::{self, prelude::*};
+use std::path::Path;
+
+const MAGIC: &[u8; 4] = b"ICS1";
+
+// Zero runs at least this long are left out of the encoded memory
+const MIN_ZERO_GAP: usize = 4;
+
+// Complete state of a `Computer` apart from its tracer and memory limit,
+// taken with `Computer::snapshot` and resumed with `Computer::from_snapshot`
+// Memory is kept as runs of non-zero words, so a snapshot of paged memory is only as large as what was written
+//
+// Encoded as `ICS1`, a flags byte (bit 0 halted), then varints: instruction pointer, relative base,
+// pending input count and values, memory size, and the number of memory runs each stored as
+// (gap since previous run, length, values). Signed values are zigzag encoded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Snapshot {
+    pub size: usize,                  // one past the highest address in memory
+    pub runs: Vec<(usize, Vec<i64>)>, // start and words of every stretch of non-zero memory, in address order
+    pub offset: usize,
+    pub relative_offset: i64,
+    pub input: Vec<i64>,
+    pub halted: bool,
+}
+
+impl Snapshot {
+    // Word at `address`, 0 outside every run
+    pub fn get(&self, address: usize) -> i64 {
+        let i = self.runs.partition_point(|(start, _)| *start <= address);
+        match i.checked_sub(1).map(|i| &self.runs[i]) {
+            Some((start, words)) => words.get(address - start).copied().unwrap_or(0),
+            None => 0,
+        }
+    }
+
+    pub fn write_to<W: Write + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(MAGIC)?;
+        w.write_all(&[u8::from(self.halted)])?;
+        varint::write_u64(w, self.offset as u64)?;
+        varint::write_i64(w, self.relative_offset)?;
+        varint::write_u64(w, self.input.len() as u64)?;
+        for &val in &self.input {
+            varint::write_i64(w, val)?;
+        }
+
+        varint::write_u64(w, self.size as u64)?;
+        varint::write_u64(w, self.runs.len() as u64)?;
+        let mut end = 0;
+        for (start, words) in &self.runs {
+            varint::write_u64(w, (start - end) as u64)?;
+            varint::write_u64(w, words.len() as u64)?;
+            for &val in words {
+                varint::write_i64(w, val)?;
+            }
+            end = start + words.len();
+        }
+        Ok(())
+    }
+
+    // Memory is only ever allocated for words actually present in `r`, whatever size and gaps it claims,
+    // and `Computer::from_snapshot` checks the size against a memory limit before restoring it
+    pub fn read_from<R: Read + ?Sized>(r: &mut R) -> io::Result<Self> {
+        let mut header = [0; 5];
+        r.read_exact(&mut header)?;
+        if &header[..4] != MAGIC {
+            return Err(invalid("not an Intcode snapshot"));
+        }
+        if header[4] > 1 {
+            return Err(invalid("unknown snapshot flags"));
+        }
+        let halted = header[4] == 1;
+        let offset = to_usize(varint::expect_u64(r)?)?;
+        let relative_offset = varint::expect_i64(r)?;
+        let input_len = varint::expect_u64(r)?;
+        let mut input = Vec::new();
+        for _ in 0..input_len {
+            input.push(varint::expect_i64(r)?);
+        }
+
+        let size = to_usize(varint::expect_u64(r)?)?;
+        let run_count = varint::expect_u64(r)?;
+        let mut runs = Vec::new();
+        let mut end: usize = 0;
+        for _ in 0..run_count {
+            let gap = to_usize(varint::expect_u64(r)?)?;
+            let len = to_usize(varint::expect_u64(r)?)?;
+            let start = end.checked_add(gap);
+            match start.and_then(|start| start.checked_add(len)) {
+                Some(end) if end <= size => {}
+                _ => return Err(invalid("memory run past end of memory")),
+            }
+            let start = end + gap;
+            let mut words = Vec::new();
+            for _ in 0..len {
+                words.push(varint::expect_i64(r)?);
+            }
+            runs.push((start, words));
+            end = start + len;
+        }
+
+        Ok(Self {
+            size,
+            runs,
+            offset,
+            relative_offset,
+            input,
+            halted,
+        })
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut w = io::BufWriter::new(std::fs::File::create(path)?);
+        self.write_to(&mut w)?;
+        w.flush()
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut r = io::BufReader::new(std::fs::File::open(path)?);
+        Self::read_from(&mut r)
+    }
+}
+
+// Every stretch of non-zero words in `chunks`, split wherever at least `MIN_ZERO_GAP` zeros or
+// addresses outside every chunk separate them
+pub(crate) fn runs(chunks: &[(usize, &[i64])]) -> Vec<(usize, Vec<i64>)> {
+    let mut runs: Vec<(usize, Vec<i64>)> = Vec::new();
+    let mut end = 0; // one past the last non-zero word so far
+    for &(start, words) in chunks {
+        for (pos, &val) in (start..).zip(words) {
+            if val == 0 {
+                continue;
+            }
+            match runs.last_mut() {
+                Some((run_start, run)) if pos - end < MIN_ZERO_GAP => {
+                    run.resize(pos - *run_start, 0);
+                    run.push(val);
+                }
+                _ => runs.push((pos, vec![val])),
+            }
+            end = pos + 1;
+        }
+    }
+    runs
+}
+
+fn to_usize(val: u64) -> io::Result<usize> {
+    if val > usize::MAX as u64 {
+        Err(invalid("value too large for this platform"))
+    } else {
+        Ok(val as usize)
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut memory = vec![1, 2, 0, 3, 0, 0, 0, 0, 0, 4, -5];
+        memory.resize(1000, 0);
+        memory.push(7);
+        memory.resize(1010, 0);
+        let snapshot = Snapshot {
+            size: memory.len(),
+            runs: runs(&[(0, &memory)]),
+            offset: 9,
+            relative_offset: -20,
+            input: vec![6, -7],
+            halted: false,
+        };
+
+        assert_eq!(
+            vec![(0, vec![1, 2, 0, 3]), (9, vec![4, -5]), (1000, vec![7])],
+            snapshot.runs
+        );
+        assert_eq!(3, snapshot.get(3));
+        assert_eq!(0, snapshot.get(5));
+        assert_eq!(7, snapshot.get(1000));
+        assert_eq!(0, snapshot.get(5000));
+
+        let mut buf = Vec::new();
+        snapshot.write_to(&mut buf).unwrap();
+        assert!(buf.len() < 40);
+        assert_eq!(snapshot, Snapshot::read_from(&mut &buf[..]).unwrap());
+
+        assert!(Snapshot::read_from(&mut &buf[..buf.len() - 1]).is_err());
+        buf[0] = b'X';
+        assert!(Snapshot::read_from(&mut &buf[..]).is_err());
+    }
+
+    #[test]
+    fn runs_across_chunks() {
+        let chunks: [(usize, &[i64]); 3] = [(0, &[0, 1, 0]), (3, &[0, 2]), (1 << 40, &[3, 0])];
+        assert_eq!(
+            vec![(1, vec![1, 0, 0, 2]), (1 << 40, vec![3])],
+            runs(&chunks)
+        );
+    }
+
+    #[test]
+    fn corrupt() {
+        // huge sizes, gaps and lengths are errors or sparse, never allocated
+        let header = |size: u64, gap: u64, len: u64| {
+            let mut buf = b"ICS1\0\0\0\0".to_vec();
+            for &val in &[size, 1, gap, len] {
+                varint::write_u64(&mut buf, val).unwrap();
+            }
+            buf
+        };
+
+        let mut buf = header(u64::MAX >> 1, 1 << 60, 1);
+        varint::write_i64(&mut buf, 5).unwrap();
+        let snapshot = Snapshot::read_from(&mut &buf[..]).unwrap();
+        assert_eq!(vec![(1 << 60, vec![5])], snapshot.runs);
+
+        assert!(Snapshot::read_from(&mut &header(10, 8, 3)[..]).is_err());
+        assert!(Snapshot::read_from(&mut &header(u64::MAX >> 1, 0, u64::MAX >> 2)[..]).is_err());
+    }
+}
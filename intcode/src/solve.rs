@@ -0,0 +1,200 @@
+use crate::{pool, symbolic, Computer, Memory};
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// Instructions each candidate may run before it's taken not to be a solution, so one that loops forever can't hang
+pub const MAX_STEPS: u64 = 1_000_000;
+
+// Lazily yields every way of picking one value from each range, in lexicographic order
+pub(crate) struct Candidates {
+    ranges: Vec<Range<i64>>,
+    current: Option<Vec<i64>>,
+}
+
+impl Candidates {
+    pub fn new(ranges: &[Range<i64>]) -> Self {
+        let current = if ranges.iter().any(|r| r.is_empty()) {
+            None
+        } else {
+            Some(ranges.iter().map(|r| r.start).collect())
+        };
+        Self {
+            ranges: ranges.to_vec(),
+            current,
+        }
+    }
+}
+
+impl Iterator for Candidates {
+    type Item = Vec<i64>;
+
+    fn next(&mut self) -> Option<Vec<i64>> {
+        let current = self.current.take()?;
+        let mut next = current.clone();
+        for i in (0..next.len()).rev() {
+            next[i] += 1;
+            if next[i] < self.ranges[i].end {
+                self.current = Some(next);
+                break;
+            }
+            next[i] = self.ranges[i].start;
+        }
+        Some(current)
+    }
+}
+
+// Finds values for the patched addresses, each taken from its range, that leave `target` at `address`
+// once the program halts, like the noun and verb in day 2
+// Each patch is (address, range) and the lexicographically smallest solution is returned
+// If the program is straight-line `add`/`mul` the relationship is solved symbolically,
+// otherwise every candidate is run across all available cores
+pub fn solve<M: Memory<Cell = i64> + Clone + Send>(
+    template: &Computer<M>,
+    patches: &[(usize, Range<i64>)],
+    address: usize,
+    target: i64,
+) -> Option<Vec<i64>> {
+    match solve_symbolic(template, patches, address, target) {
+        Some(solution) => solution,
+        None => search(template, patches, address, target),
+    }
+}
+
+// Like `solve` but always runs every candidate
+pub fn search<M: Memory<Cell = i64> + Clone + Send>(
+    template: &Computer<M>,
+    patches: &[(usize, Range<i64>)],
+    address: usize,
+    target: i64,
+) -> Option<Vec<i64>> {
+    let ranges: Vec<_> = patches.iter().map(|(_, range)| range.clone()).collect();
+    let found = AtomicBool::new(false);
+    let results = pool::run_workers(
+        Candidates::new(&ranges),
+        || template.clone(),
+        |template, candidates| loop {
+            // candidates are handed out in order, so once one works only those already taken can beat it
+            if found.load(Ordering::Relaxed) {
+                return None;
+            }
+            let values = candidates.next()?;
+            if check(&template, patches, &values, address, target) {
+                found.store(true, Ordering::Relaxed);
+                return Some(values);
+            }
+        },
+    );
+    results.into_iter().flatten().min()
+}
+
+// Solves for the patched values using `symbolic::analyze`, returning `None` if the program isn't straight-line
+// or the target cell can't be written in terms of the patched values
+// Every solution is confirmed by actually running it
+fn solve_symbolic<M: Memory<Cell = i64> + Clone>(
+    template: &Computer<M>,
+    patches: &[(usize, Range<i64>)],
+    address: usize,
+    target: i64,
+) -> Option<Option<Vec<i64>>> {
+    let symbols: Vec<(usize, &str)> = patches.iter().map(|&(pos, _)| (pos, "")).collect();
+    let analysis = symbolic::analyze(template, &symbols).ok()?;
+    let ranges: Vec<_> = patches.iter().map(|(_, range)| range.clone()).collect();
+    let expr = analysis.cell(address)?;
+    let solution = expr
+        .solutions(target, &ranges)
+        .find(|values| check(template, patches, values, address, target));
+    Some(solution)
+}
+
+// Whether patching in `values` makes the program halt with `target` at `address` within `MAX_STEPS` instructions
+fn check<M: Memory<Cell = i64> + Clone>(
+    template: &Computer<M>,
+    patches: &[(usize, Range<i64>)],
+    values: &[i64],
+    address: usize,
+    target: i64,
+) -> bool {
+    let mut c = template.clone();
+    c.set_instruction_limit(Some(c.instructions() + MAX_STEPS));
+    for ((pos, _), &val) in patches.iter().zip(values) {
+        c.set_memory(*pos, val);
+    }
+    c.run(&[]).is_ok() && c.halted() && c.memory().get(address) == target
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // mem[0] = 300 * mem[1] + mem[2] + 7, reading through mem[1] and mem[2] first like day 2
+    const LINEAR: [i64; 21] = [
+        1, 0, 0, 3, 1002, 1, 300, 22, 1, 22, 2, 22, 1101, 0, 7, 23, 1, 22, 23, 0, 99,
+    ];
+
+    #[test]
+    fn candidates() {
+        let all: Vec<_> = Candidates::new(&[0..2, 5..8]).collect();
+        assert_eq!(
+            vec![
+                vec![0, 5],
+                vec![0, 6],
+                vec![0, 7],
+                vec![1, 5],
+                vec![1, 6],
+                vec![1, 7]
+            ],
+            all
+        );
+        assert_eq!(0, Candidates::new(&[0..2, 3..3]).count());
+        assert_eq!(
+            vec![Vec::<i64>::new()],
+            Candidates::new(&[]).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn linear() {
+        let c = Computer::new(LINEAR.to_vec());
+        let patches = [(1, 0..100), (2, 0..100)];
+        let target = 300 * 12 + 34 + 7;
+        assert_eq!(
+            Some(Some(vec![12, 34])),
+            solve_symbolic(&c, &patches, 0, target)
+        );
+        assert_eq!(Some(vec![12, 34]), search(&c, &patches, 0, target));
+        assert_eq!(Some(None), solve_symbolic(&c, &patches, 0, 300 * 100));
+        assert_eq!(None, search(&c, &patches, 0, 300 * 100));
+    }
+
+    #[test]
+    fn looping() {
+        // mem[12] = mem[13] * mem[14] by repeated addition, which isn't straight-line
+        let data = vec![1, 12, 13, 12, 1001, 14, -1, 14, 1005, 14, 0, 99, 0, 0, 0];
+        let c = Computer::new(data);
+        let patches = [(13, 0..10), (14, 1..10)];
+        assert_eq!(None, solve_symbolic(&c, &patches, 12, 42));
+        assert_eq!(Some(vec![6, 7]), solve(&c, &patches, 12, 42));
+    }
+
+    #[test]
+    fn looping_candidate() {
+        // loops forever at 0 while mem[5] is 0, otherwise halts leaving mem[4] untouched
+        let c = Computer::new(vec![1006, 5, 0, 99, 0, 0]);
+        assert_eq!(Some(vec![1]), solve(&c, &[(5, 0..2)], 4, 0));
+        assert_eq!(None, search(&c, &[(5, 0..2)], 4, 1));
+
+        // loops unless mem[9] is 0, then sets mem[8] = mem[9] + 5, so the first candidate loops
+        let c = Computer::new(vec![1005, 9, 0, 1001, 9, 5, 8, 99, 0, 0]);
+        assert_eq!(Some(vec![0]), search(&c, &[(9, -1..3)], 8, 5));
+    }
+
+    #[test]
+    fn symbolic_address() {
+        // mem[0] = mem[mem[1]] + mem[mem[2]], read through the patched addresses themselves
+        let c = Computer::new(vec![1, 0, 0, 0, 99]);
+        let patches = [(1, 0..5), (2, 0..5)];
+        assert_eq!(None, solve_symbolic(&c, &patches, 0, 198));
+        assert_eq!(Some(vec![4, 4]), solve(&c, &patches, 0, 198));
+        assert_eq!(Some(vec![0, 1]), solve(&c, &patches, 0, 1));
+    }
+}
@@ -0,0 +1,557 @@
+use crate::solve::Candidates;
+use crate::{decode, Computer, IntcodeError, Memory, OPCODES};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops::Range;
+
+// Products with more terms than this are treated as unknown rather than expanded
+const MAX_TERMS: usize = 1024;
+
+// Polynomial over the symbols of an `Analysis` with `i64` coefficients
+// Each term is keyed by its monomial, the sorted indices of the symbols multiplied together,
+// so the constant term is keyed by `[]` and `noun * noun * verb` by `[0, 0, 1]`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Expr {
+    terms: BTreeMap<Vec<usize>, i64>,
+}
+
+impl Expr {
+    pub fn constant(val: i64) -> Self {
+        let mut e = Self::default();
+        if val != 0 {
+            e.terms.insert(Vec::new(), val);
+        }
+        e
+    }
+
+    pub fn symbol(i: usize) -> Self {
+        let mut e = Self::default();
+        e.terms.insert(vec![i], 1);
+        e
+    }
+
+    // The value if no symbol contributes to it
+    pub fn as_constant(&self) -> Option<i64> {
+        match self.terms.iter().next() {
+            None => Some(0),
+            Some((monomial, &c)) if monomial.is_empty() && self.terms.len() == 1 => Some(c),
+            Some(_) => None,
+        }
+    }
+
+    // Highest number of symbols multiplied together in any term
+    pub fn degree(&self) -> usize {
+        self.terms.keys().map(Vec::len).max().unwrap_or(0)
+    }
+
+    // Coefficient of the term that is just `symbol`, 0 if there isn't one
+    pub fn coefficient(&self, symbol: usize) -> i64 {
+        self.terms.get(&vec![symbol]).copied().unwrap_or(0)
+    }
+
+    // Value with `values[i]` substituted for symbol `i`, or `None` if it doesn't fit in an `i64`
+    // or a symbol it uses has no value
+    pub fn eval(&self, values: &[i64]) -> Option<i64> {
+        let mut sum: i128 = 0;
+        for (monomial, &c) in &self.terms {
+            let mut term = i128::from(c);
+            for &s in monomial {
+                term = term.checked_mul(i128::from(*values.get(s)?))?;
+            }
+            sum = sum.checked_add(term)?;
+        }
+        i64::try_from(sum).ok()
+    }
+
+    fn add(&self, other: &Self) -> Option<Self> {
+        let mut sum = self.clone();
+        for (monomial, &c) in &other.terms {
+            sum.add_term(monomial.clone(), c)?;
+        }
+        Some(sum)
+    }
+
+    fn mul(&self, other: &Self) -> Option<Self> {
+        let mut product = Self::default();
+        for (a, &ca) in &self.terms {
+            for (b, &cb) in &other.terms {
+                let mut monomial = a.clone();
+                monomial.extend(b);
+                monomial.sort_unstable();
+                product.add_term(monomial, ca.checked_mul(cb)?)?;
+                if product.terms.len() > MAX_TERMS {
+                    return None;
+                }
+            }
+        }
+        Some(product)
+    }
+
+    fn add_term(&mut self, monomial: Vec<usize>, c: i64) -> Option<()> {
+        let total = self
+            .terms
+            .get(&monomial)
+            .copied()
+            .unwrap_or(0)
+            .checked_add(c)?;
+        if total == 0 {
+            self.terms.remove(&monomial);
+        } else {
+            self.terms.insert(monomial, total);
+        }
+        Some(())
+    }
+
+    // Every assignment of symbol values, each taken from its range, that makes the expression equal `target`,
+    // in lexicographic order
+    // Symbols the expression doesn't use are pinned to the start of their range. When the highest used symbol
+    // appears at most once per term it's solved for directly, otherwise every assignment is evaluated.
+    // There are no solutions if a symbol the expression uses has no range.
+    pub fn solutions<'a>(
+        &'a self,
+        target: i64,
+        ranges: &[Range<i64>],
+    ) -> Box<dyn Iterator<Item = Vec<i64>> + 'a> {
+        let used: BTreeSet<usize> = self.terms.keys().flatten().copied().collect();
+        if used.iter().any(|&s| s >= ranges.len()) {
+            return Box::new(std::iter::empty());
+        }
+        let mut ranges: Vec<Range<i64>> = ranges
+            .iter()
+            .enumerate()
+            .map(|(i, r)| {
+                if used.contains(&i) || r.is_empty() {
+                    r.clone()
+                } else {
+                    r.start..r.start + 1
+                }
+            })
+            .collect();
+
+        let last = match used.iter().next_back() {
+            Some(&last)
+                if self
+                    .terms
+                    .keys()
+                    .all(|monomial| monomial.iter().filter(|&&s| s == last).count() <= 1) =>
+            {
+                last
+            }
+            _ => {
+                return Box::new(
+                    Candidates::new(&ranges)
+                        .filter(move |values| self.eval(values) == Some(target)),
+                )
+            }
+        };
+
+        // target = a * x + b where a and b only involve the other symbols
+        let mut a = Self::default();
+        let mut b = Self::default();
+        for (monomial, &c) in &self.terms {
+            match monomial.iter().position(|&s| s == last) {
+                Some(i) => {
+                    let mut rest = monomial.clone();
+                    rest.remove(i);
+                    a.terms.insert(rest, c);
+                }
+                None => {
+                    b.terms.insert(monomial.clone(), c);
+                }
+            }
+        }
+        let range = ranges[last].clone();
+        if !range.is_empty() {
+            ranges[last] = range.start..range.start + 1;
+        }
+        Box::new(Candidates::new(&ranges).filter_map(move |mut values| {
+            let a = i128::from(a.eval(&values)?);
+            let rest = i128::from(target) - i128::from(b.eval(&values)?);
+            let x = if a == 0 {
+                if rest != 0 {
+                    return None;
+                }
+                range.start
+            } else {
+                if rest % a != 0 {
+                    return None;
+                }
+                i64::try_from(rest / a).ok().filter(|x| range.contains(x))?
+            };
+            values[last] = x;
+            Some(values)
+        }))
+    }
+
+    // First of `solutions`
+    pub fn solve(&self, target: i64, ranges: &[Range<i64>]) -> Option<Vec<i64>> {
+        self.solutions(target, ranges).next()
+    }
+
+    // Writes the expression using `names` for the symbols, highest degree terms first
+    // Symbols without a name are written as `$` and their index
+    fn write(&self, f: &mut fmt::Formatter, names: &[String]) -> fmt::Result {
+        if self.terms.is_empty() {
+            return write!(f, "0");
+        }
+        let mut terms: Vec<_> = self.terms.iter().collect();
+        terms.sort_by(|(a, _), (b, _)| b.len().cmp(&a.len()).then(a.cmp(b)));
+        for (i, (monomial, &c)) in terms.into_iter().enumerate() {
+            let magnitude = c.unsigned_abs();
+            match (i, c < 0) {
+                (0, true) => write!(f, "-")?,
+                (0, false) => {}
+                (_, true) => write!(f, " - ")?,
+                (_, false) => write!(f, " + ")?,
+            }
+            if monomial.is_empty() {
+                write!(f, "{}", magnitude)?;
+                continue;
+            }
+            if magnitude != 1 {
+                write!(f, "{}*", magnitude)?;
+            }
+            let mut factors: Vec<(usize, usize)> = Vec::new();
+            for &s in monomial {
+                match factors.last_mut() {
+                    Some((last, power)) if *last == s => *power += 1,
+                    _ => factors.push((s, 1)),
+                }
+            }
+            for (j, (s, power)) in factors.into_iter().enumerate() {
+                if j > 0 {
+                    write!(f, "*")?;
+                }
+                match names.get(s) {
+                    Some(name) => write!(f, "{}", name)?,
+                    None => write!(f, "${}", s)?,
+                }
+                if power > 1 {
+                    write!(f, "^{}", power)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// Reasons a program can't be analyzed symbolically
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SymbolicError {
+    // Only `add`, `mul` and halt are straight-line
+    Unsupported { opcode: i64, offset: usize },
+    // The instruction or its write address depends on a symbol
+    SymbolicCode { offset: usize },
+    Intcode(IntcodeError),
+}
+
+impl fmt::Display for SymbolicError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Unsupported { opcode, offset } => {
+                write!(f, "Unsupported opcode {} at {}", opcode, offset)
+            }
+            Self::SymbolicCode { offset } => {
+                write!(f, "Instruction at {} depends on a symbol", offset)
+            }
+            Self::Intcode(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for SymbolicError {}
+
+impl From<IntcodeError> for SymbolicError {
+    fn from(e: IntcodeError) -> Self {
+        Self::Intcode(e)
+    }
+}
+
+// Result of running a program with some memory cells replaced by symbols
+// Cells whose value can't be written as an `Expr`, like ones read through an address that depends on a symbol
+// or with a coefficient that overflows, are unknown
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Analysis {
+    names: Vec<String>,
+    base: HashMap<usize, i64>, // non-zero words of memory before the program ran
+    cells: HashMap<usize, Option<Expr>>, // every symbol and written cell, `None` if unknown
+    written: BTreeSet<usize>,
+}
+
+impl Analysis {
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    // Final value of the cell at `address`, or `None` if it's unknown
+    pub fn cell(&self, address: usize) -> Option<Expr> {
+        match self.cells.get(&address) {
+            Some(e) => e.clone(),
+            None => Some(Expr::constant(
+                self.base.get(&address).copied().unwrap_or(0),
+            )),
+        }
+    }
+
+    // Addresses written by the program, in order
+    pub fn written(&self) -> impl Iterator<Item = usize> + '_ {
+        self.written.iter().copied()
+    }
+
+    // First assignment of the symbols, each taken from its range, that leaves `target` at `address`
+    // See `Expr::solutions`, there's never a solution if the cell is unknown
+    // or `ranges` doesn't have one range per symbol
+    pub fn solve(&self, address: usize, target: i64, ranges: &[Range<i64>]) -> Option<Vec<i64>> {
+        if ranges.len() != self.names.len() {
+            return None;
+        }
+        self.cell(address)?.solve(target, ranges)
+    }
+
+    // Writes `e` using this analysis' symbol names
+    pub fn format(&self, e: &Expr) -> String {
+        struct Named<'a>(&'a Expr, &'a [String]);
+        impl fmt::Display for Named<'_> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                self.0.write(f, self.1)
+            }
+        }
+        Named(e, &self.names).to_string()
+    }
+
+    // Value at `pos` if it doesn't depend on any symbol
+    fn constant(&self, pos: usize, offset: usize) -> Result<i64, SymbolicError> {
+        match self.cell(pos).as_ref().and_then(Expr::as_constant) {
+            Some(val) => Ok(val),
+            None => Err(SymbolicError::SymbolicCode { offset }),
+        }
+    }
+
+    // Address a positional or relative param at `pos` refers to, `None` if it depends on a symbol
+    fn address(
+        &self,
+        pos: usize,
+        mode: i64,
+        relative_offset: i64,
+        offset: usize,
+    ) -> Result<Option<usize>, SymbolicError> {
+        let param = match self.cell(pos).as_ref().and_then(Expr::as_constant) {
+            Some(param) => param,
+            None => return Ok(None),
+        };
+        let address = match mode {
+            0 => param,
+            2 => match param.checked_add(relative_offset) {
+                Some(address) => address,
+                None => return Err(IntcodeError::Overflow { offset }.into()),
+            },
+            _ => return Err(IntcodeError::InvalidMode { mode, offset }.into()),
+        };
+        if address < 0 {
+            return Err(IntcodeError::NegativeAddress { address, offset }.into());
+        }
+        Ok(Some(address as usize))
+    }
+
+    // Value of the param at `pos`, `None` if it's read through an address that depends on a symbol
+    fn operand(
+        &self,
+        pos: usize,
+        mode: i64,
+        relative_offset: i64,
+        offset: usize,
+    ) -> Result<Option<Expr>, SymbolicError> {
+        if mode == 1 {
+            return Ok(self.cell(pos));
+        }
+        Ok(match self.address(pos, mode, relative_offset, offset)? {
+            Some(address) => self.cell(address),
+            None => None,
+        })
+    }
+}
+
+impl fmt::Display for Analysis {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for &address in &self.written {
+            write!(f, "mem[{}] = ", address)?;
+            match &self.cells[&address] {
+                Some(e) => e.write(f, &self.names)?,
+                None => write!(f, "?")?,
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+// Runs `computer`'s program from its current state with the cells in `symbols` replaced by named symbols,
+// producing the final value of every written cell in terms of them, like `mem[0] = 300000*noun + verb + 29848`
+// Only straight-line programs, a run of `add`/`mul` ending in a halt, can be analyzed
+pub fn analyze<M: Memory<Cell = i64>>(
+    computer: &Computer<M>,
+    symbols: &[(usize, &str)],
+) -> Result<Analysis, SymbolicError> {
+    let mut analysis = Analysis {
+        names: symbols.iter().map(|(_, name)| name.to_string()).collect(),
+        base: computer
+            .memory()
+            .populated()
+            .into_iter()
+            .flat_map(|(start, words)| (start..).zip(words.iter().copied()))
+            .filter(|&(_, val)| val != 0)
+            .collect(),
+        cells: HashMap::new(),
+        written: BTreeSet::new(),
+    };
+    for (i, &(address, _)) in symbols.iter().enumerate() {
+        analysis.cells.insert(address, Some(Expr::symbol(i)));
+    }
+    if computer.halted() {
+        return Ok(analysis);
+    }
+    let rb = computer.relative_offset();
+
+    let mut offset = computer.offset();
+    loop {
+        let instruction = analysis.constant(offset, offset)?;
+        let (opcode, modes) = match decode(instruction) {
+            Some(decoded) => decoded,
+            None => {
+                return Err(IntcodeError::UnknownOpcode {
+                    opcode: instruction,
+                    offset,
+                }
+                .into())
+            }
+        };
+        match opcode {
+            1 | 2 => {
+                let a = analysis.operand(offset + 1, modes[0], rb, offset)?;
+                let b = analysis.operand(offset + 2, modes[1], rb, offset)?;
+                let val = match (a, b) {
+                    (Some(a), Some(b)) if opcode == 1 => a.add(&b),
+                    (Some(a), Some(b)) => a.mul(&b),
+                    _ => None,
+                };
+                if modes[2] == 1 {
+                    return Err(IntcodeError::ImmediateWrite { offset }.into());
+                }
+                let pos = match analysis.address(offset + 3, modes[2], rb, offset)? {
+                    Some(pos) => pos,
+                    None => return Err(SymbolicError::SymbolicCode { offset }),
+                };
+                analysis.cells.insert(pos, val);
+                analysis.written.insert(pos);
+                offset += 4;
+            }
+            99 => return Ok(analysis),
+            _ if OPCODES.iter().any(|&(op, ..)| op == opcode) => {
+                return Err(SymbolicError::Unsupported { opcode, offset })
+            }
+            _ => {
+                return Err(IntcodeError::UnknownOpcode {
+                    opcode: instruction,
+                    offset,
+                }
+                .into())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn linear() {
+        // mem[0] = 3 * mem[1] + mem[2] + 4, with a throwaway read through mem[1] and mem[2] first
+        let data = vec![
+            1, 0, 0, 3, 1002, 1, 3, 22, 1, 22, 2, 22, 1101, 0, 4, 23, 1, 22, 23, 0, 99,
+        ];
+        let analysis = analyze(&Computer::new(data), &[(1, "noun"), (2, "verb")]).unwrap();
+        assert_eq!(
+            "mem[0] = 3*noun + verb + 4\nmem[3] = ?\nmem[22] = 3*noun + verb\nmem[23] = 4\n",
+            analysis.to_string()
+        );
+        let e = analysis.cell(0).unwrap();
+        assert_eq!(1, e.degree());
+        assert_eq!(3, e.coefficient(0));
+        assert_eq!(Some(3 * 12 + 34 + 4), e.eval(&[12, 34]));
+        assert_eq!(None, analysis.cell(3));
+        assert_eq!(Some(Expr::constant(99)), analysis.cell(20));
+        assert_eq!(vec![0, 3, 22, 23], analysis.written().collect::<Vec<_>>());
+
+        assert_eq!(
+            Some(vec![0, 70]),
+            analysis.solve(0, 3 * 12 + 34 + 4, &[0..100, 0..100])
+        );
+        assert_eq!(None, analysis.solve(0, 500, &[0..100, 0..100]));
+        assert_eq!(None, analysis.solve(3, 0, &[0..100, 0..100]));
+        assert_eq!(
+            vec![vec![0, 4], vec![1, 1]],
+            e.solutions(8, &[0..10, 0..10]).collect::<Vec<_>>()
+        );
+
+        // a range per symbol is needed, and unnamed symbols are written by index
+        assert_eq!(None, analysis.solve(0, 40, &[0..100, 0..100][..1]));
+        assert_eq!(None, e.solve(40, &[0..100, 0..100][..1]));
+        assert_eq!(None, e.eval(&[12]));
+        let e = Expr::symbol(5).add(&Expr::constant(1)).unwrap();
+        assert_eq!("$5 + 1", analysis.format(&e));
+    }
+
+    #[test]
+    fn polynomial() {
+        // mem[42] = (a - b) * (a + b), mem[43] = a * a * a - 2
+        let data = vec![
+            1002, 31, -1, 40, 1, 30, 40, 40, 1, 30, 31, 41, 2, 40, 41, 42, 2, 30, 30, 43, 2, 43,
+            30, 43, 1001, 43, -2, 43, 99,
+        ];
+        let analysis = analyze(&Computer::new(data), &[(30, "a"), (31, "b")]).unwrap();
+        let e = analysis.cell(42).unwrap();
+        assert_eq!(2, e.degree());
+        assert_eq!("a^2 - b^2", analysis.format(&e));
+        assert_eq!("a^3 - 2", analysis.format(&analysis.cell(43).unwrap()));
+        // b is squared so can't be solved for directly and every pair is tried
+        assert_eq!(Some(vec![4, 1]), e.solve(15, &[0..10, 0..10]));
+        assert_eq!(Some(vec![3, 0]), analysis.solve(43, 25, &[-5..5, 0..3]));
+    }
+
+    #[test]
+    fn unsupported() {
+        let analyze = |data: &[i64], symbols| analyze(&Computer::new(data.to_vec()), symbols);
+        assert_eq!(
+            Err(SymbolicError::SymbolicCode { offset: 0 }),
+            analyze(&[1, 0, 0, 3, 99], &[(3, "x")])
+        );
+        assert_eq!(
+            Err(SymbolicError::SymbolicCode { offset: 4 }),
+            analyze(&[1, 6, 6, 4, 99, 0, 0], &[(6, "x")])
+        );
+        assert_eq!(
+            Err(SymbolicError::Unsupported {
+                opcode: 5,
+                offset: 0
+            }),
+            analyze(&[1105, 1, 0], &[])
+        );
+        assert_eq!(
+            Err(SymbolicError::Intcode(IntcodeError::UnknownOpcode {
+                opcode: 0,
+                offset: 4
+            })),
+            analyze(&[1, 0, 0, 0], &[])
+        );
+
+        // relative base near the top of the range
+        let mut c = Computer::new(vec![109, i64::MAX, 21101, 1, 1, 1, 99]);
+        c.step().unwrap();
+        assert_eq!(
+            Err(SymbolicError::Intcode(IntcodeError::Overflow { offset: 2 })),
+            super::analyze(&c, &[])
+        );
+    }
+}
@@ -0,0 +1,39 @@
+use std::fmt;
+
+// Record of a single executed instruction, passed to the tracer set with `Computer::set_tracer`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Trace<T = i64> {
+    pub offset: usize,
+    pub opcode: i64,
+    pub modes: [i64; 3],
+    pub operands: Vec<T>, // resolved values of every read parameter, in order
+    pub writes: Vec<(usize, T)>, // (address, value) for every memory write
+}
+
+impl<T> Trace<T> {
+    pub(crate) fn new(offset: usize, opcode: i64, modes: [i64; 3]) -> Self {
+        Self {
+            offset,
+            opcode,
+            modes,
+            operands: Vec::new(),
+            writes: Vec::new(),
+        }
+    }
+}
+
+impl<T: fmt::Debug + fmt::Display> fmt::Display for Trace<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:>6}: opcode {:>2} modes {:?} operands {:?}",
+            self.offset, self.opcode, self.modes, self.operands
+        )?;
+        for (address, val) in &self.writes {
+            write!(f, " [{}] <- {}", address, val)?;
+        }
+        Ok(())
+    }
+}
+
+pub(crate) type Tracer<T> = Box<dyn FnMut(&Trace<T>) + Send>;
@@ -0,0 +1,108 @@
+// LEB128 style variable length integers: 7 bits per byte, least significant group first,
+// high bit set on every byte but the last. Signed values are zigzag encoded first so small
+// negative numbers stay short.
+
+use std::io::{self, prelude::*};
+
+pub(crate) fn write_u64<W: Write + ?Sized>(w: &mut W, mut val: u64) -> io::Result<()> {
+    loop {
+        let byte = (val & 0x7f) as u8;
+        val >>= 7;
+        if val == 0 {
+            return w.write_all(&[byte]);
+        }
+        w.write_all(&[byte | 0x80])?;
+    }
+}
+
+pub(crate) fn write_i64<W: Write + ?Sized>(w: &mut W, val: i64) -> io::Result<()> {
+    write_u64(w, ((val << 1) ^ (val >> 63)) as u64)
+}
+
+// Returns `Ok(None)` at a clean end of input, before the first byte of a value
+pub(crate) fn read_u64<R: Read + ?Sized>(r: &mut R) -> io::Result<Option<u64>> {
+    let mut val = 0;
+    let mut shift = 0;
+    loop {
+        let mut byte = [0];
+        if r.read(&mut byte)? == 0 {
+            if shift == 0 {
+                return Ok(None);
+            }
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "truncated varint",
+            ));
+        }
+        if shift >= 64 || (shift == 63 && byte[0] & 0x7e != 0) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "varint overflows 64 bits",
+            ));
+        }
+        val |= u64::from(byte[0] & 0x7f) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(Some(val));
+        }
+        shift += 7;
+    }
+}
+
+pub(crate) fn read_i64<R: Read + ?Sized>(r: &mut R) -> io::Result<Option<i64>> {
+    Ok(read_u64(r)?.map(|val| (val >> 1) as i64 ^ -((val & 1) as i64)))
+}
+
+// Like `read_u64` but running out of input is an error
+pub(crate) fn expect_u64<R: Read + ?Sized>(r: &mut R) -> io::Result<u64> {
+    match read_u64(r)? {
+        Some(val) => Ok(val),
+        None => Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "unexpected end of input",
+        )),
+    }
+}
+
+// Like `read_i64` but running out of input is an error
+pub(crate) fn expect_i64<R: Read + ?Sized>(r: &mut R) -> io::Result<i64> {
+    match read_i64(r)? {
+        Some(val) => Ok(val),
+        None => Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "unexpected end of input",
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let vals = [
+            0,
+            1,
+            -1,
+            63,
+            -64,
+            64,
+            1_125_899_906_842_624,
+            i64::MAX,
+            i64::MIN,
+        ];
+        let mut buf = Vec::new();
+        for &val in &vals {
+            write_i64(&mut buf, val).unwrap();
+        }
+        assert_eq!(&[0, 2, 1, 126, 127, 128, 1], &buf[..7]);
+
+        let mut r = &buf[..];
+        for &val in &vals {
+            assert_eq!(Some(val), read_i64(&mut r).unwrap());
+        }
+        assert_eq!(None, read_i64(&mut r).unwrap());
+        assert!(read_u64(&mut &[0x80][..]).is_err());
+        assert!(read_u64(&mut &[0xff; 11][..]).is_err());
+    }
+}
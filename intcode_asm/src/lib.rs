@@ -0,0 +1,775 @@
+// Assembler for the listing syntax produced by `intcode::disasm`
+//
+//   start:                   label definition, may share a line with a statement
+//       12: add [x], #5, rb-3 numeric prefixes (listing addresses) are ignored
+//       jf [flag], #start     operands are `[position]`, `#immediate` or `rb+relative`
+//   .const SIZE 16           named constant, usable anywhere a value is
+//   x:  .data 0, SIZE, x+1   raw words
+//   .macro inc addr          macro with comma separated parameters, each replaced by the text of
+//       add addr, #1, addr   its argument wherever it appears as an identifier in the body
+//   .endm
+//       inc [x]              expands to `add [x], #1, [x]`
+//
+// Values are sums and differences of integers, labels and constants. `;` starts a comment.
+// Labels defined in a macro body are global, so a macro that defines one can only be used once.
+
+use intcode::OPCODES;
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AsmError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for AsmError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for AsmError {}
+
+// Assembles `src` into a program ready for `intcode::Computer::new`
+pub fn assemble(src: &str) -> Result<Vec<i64>, AsmError> {
+    let mut asm = Assembler::default();
+
+    // first pass: parse every line, assigning addresses to labels, evaluating constants and expanding macros
+    for (i, text) in src.lines().enumerate() {
+        asm.line(i + 1, text)?;
+    }
+    if let Some((name, def)) = asm.defining {
+        return Err(AsmError {
+            line: def.line,
+            column: 1,
+            message: format!("`.macro {}` without `.endm`", name),
+        });
+    }
+
+    // second pass: resolve labels now that every address is known
+    let symbols = asm.symbols;
+    let eval = |expr: Expr, calls: &[Call]| expr.eval(&symbols).map_err(|e| expanded(e, calls));
+    let mut program = Vec::with_capacity(asm.address);
+    for (item, calls) in asm.items {
+        match item {
+            Item::Data(vals) => {
+                for val in vals {
+                    program.push(eval(val, &calls)?);
+                }
+            }
+            Item::Instruction { opcode, operands } => {
+                let mut instruction = opcode;
+                let mut place = 100;
+                for operand in &operands {
+                    instruction += operand.mode * place;
+                    place *= 10;
+                }
+                program.push(instruction);
+                for operand in operands {
+                    program.push(eval(operand.expr, &calls)?);
+                }
+            }
+        }
+    }
+
+    Ok(program)
+}
+
+// Assembles `src` into the comma separated format programs are distributed in
+pub fn assemble_to_string(src: &str) -> Result<String, AsmError> {
+    let program = assemble(src)?;
+    let words: Vec<String> = program.iter().map(i64::to_string).collect();
+    Ok(words.join(","))
+}
+
+// Macros may use other macros, this far deep, so a macro that uses itself is an error rather than a hang
+const MAX_EXPANSION_DEPTH: usize = 32;
+
+#[derive(Default)]
+struct Assembler {
+    symbols: HashMap<String, i64>,
+    macros: HashMap<String, Macro>,
+    defining: Option<(String, Macro)>, // macro whose body is being read, until `.endm`
+    items: Vec<(Item, Vec<Call>)>,     // with the macro invocations each was expanded from
+    address: usize,
+    calls: Vec<Call>, // macro invocations being expanded, outermost first
+}
+
+#[derive(Clone)]
+struct Macro {
+    line: usize,
+    params: Vec<String>,
+    body: Vec<(usize, String)>, // source line number and text
+}
+
+// Macro invocation, reported as the position of errors in the lines it expands to
+#[derive(Clone)]
+struct Call {
+    name: String,
+    line: usize,
+    column: usize,
+}
+
+// Moves an error in the expansion of `calls` to the outermost invocation, keeping where in the macro it happened
+fn expanded(mut e: AsmError, calls: &[Call]) -> AsmError {
+    for call in calls.iter().rev() {
+        e = AsmError {
+            line: call.line,
+            column: call.column,
+            message: format!(
+                "in macro `{}` at {}:{}: {}",
+                call.name, e.line, e.column, e.message
+            ),
+        };
+    }
+    e
+}
+
+impl Assembler {
+    fn line(&mut self, line: usize, text: &str) -> Result<(), AsmError> {
+        let mut cursor = Cursor::new(line, text);
+        if let Some((_, def)) = self.defining.as_mut() {
+            match cursor.text.trim() {
+                ".endm" => {
+                    let (name, def) = self.defining.take().unwrap();
+                    self.macros.insert(name, def);
+                }
+                code if code.starts_with(".macro") => {
+                    let start = cursor.text.find(".macro").unwrap();
+                    return Err(cursor.error_at(start, "macros cannot be defined inside a macro"));
+                }
+                _ => def.body.push((line, cursor.text.to_string())),
+            }
+            return Ok(());
+        }
+
+        loop {
+            cursor.skip_ws();
+            let start = cursor.pos;
+            let name = match cursor.peek() {
+                Some(c) if c.is_ascii_digit() => cursor.take_while(|c| c.is_ascii_digit()),
+                Some(c) if is_ident_start(c) => cursor.ident(),
+                _ => break,
+            };
+            cursor.skip_ws();
+            if cursor.peek() != Some(':') {
+                cursor.pos = start;
+                break;
+            }
+            cursor.pos += 1;
+            if name.starts_with(|c: char| c.is_ascii_digit()) {
+                continue; // listing address
+            }
+            if name == "rb" {
+                return Err(cursor.error_at(start, "`rb` is reserved"));
+            }
+            if self
+                .symbols
+                .insert(name.to_string(), self.address as i64)
+                .is_some()
+            {
+                return Err(cursor.error_at(start, &format!("duplicate symbol `{}`", name)));
+            }
+        }
+
+        cursor.skip_ws();
+        if cursor.at_end() {
+            return Ok(());
+        }
+        let start = cursor.pos;
+        let word = if cursor.peek() == Some('.') {
+            cursor.pos += 1;
+            format!(".{}", cursor.ident())
+        } else {
+            cursor.ident().to_string()
+        };
+
+        match word.as_str() {
+            ".data" => {
+                let mut vals = Vec::new();
+                loop {
+                    vals.push(cursor.expr()?);
+                    cursor.skip_ws();
+                    if !cursor.eat(',') {
+                        break;
+                    }
+                }
+                self.address += vals.len();
+                self.items.push((Item::Data(vals), self.calls.clone()));
+            }
+            ".const" => {
+                cursor.skip_ws();
+                let name_start = cursor.pos;
+                let name = cursor.ident();
+                if name.is_empty() {
+                    return Err(cursor.error("expected constant name"));
+                }
+                if name == "rb" {
+                    return Err(cursor.error_at(name_start, "`rb` is reserved"));
+                }
+                let val = cursor.expr()?.eval(&self.symbols)?;
+                if self.symbols.insert(name.to_string(), val).is_some() {
+                    return Err(
+                        cursor.error_at(name_start, &format!("duplicate symbol `{}`", name))
+                    );
+                }
+            }
+            ".macro" => {
+                cursor.skip_ws();
+                let name_start = cursor.pos;
+                let name = cursor.ident();
+                if name.is_empty() {
+                    return Err(cursor.error("expected macro name"));
+                }
+                if OPCODES.iter().any(|op| op.1 == name) || self.macros.contains_key(name) {
+                    return Err(
+                        cursor.error_at(name_start, &format!("duplicate instruction `{}`", name))
+                    );
+                }
+                let mut params: Vec<String> = Vec::new();
+                loop {
+                    cursor.skip_ws();
+                    if cursor.at_end() {
+                        break;
+                    }
+                    if !params.is_empty() && !cursor.eat(',') {
+                        return Err(cursor.error("expected `,`"));
+                    }
+                    cursor.skip_ws();
+                    let param_start = cursor.pos;
+                    let param = cursor.ident();
+                    if param.is_empty() {
+                        return Err(cursor.error("expected parameter name"));
+                    }
+                    if param == "rb" {
+                        return Err(cursor.error_at(param_start, "`rb` is reserved"));
+                    }
+                    if params.iter().any(|p| p == param) {
+                        return Err(cursor
+                            .error_at(param_start, &format!("duplicate parameter `{}`", param)));
+                    }
+                    params.push(param.to_string());
+                }
+                let def = Macro {
+                    line,
+                    params,
+                    body: Vec::new(),
+                };
+                self.defining = Some((name.to_string(), def));
+            }
+            ".endm" => return Err(cursor.error_at(start, "`.endm` without `.macro`")),
+            mnemonic => {
+                if let Some(def) = self.macros.get(mnemonic) {
+                    let def = def.clone();
+                    let rest = &cursor.text[cursor.pos..];
+                    cursor.pos = cursor.text.len();
+                    return self.expand(&cursor, start, mnemonic, &def, rest);
+                }
+                let &(opcode, _, count, writes) = match OPCODES.iter().find(|op| op.1 == mnemonic) {
+                    Some(op) => op,
+                    None if mnemonic.is_empty() => return Err(cursor.error("expected instruction")),
+                    None => {
+                        return Err(
+                            cursor.error_at(start, &format!("unknown instruction `{}`", mnemonic))
+                        )
+                    }
+                };
+                let mut operands = Vec::new();
+                for i in 0..count {
+                    if i > 0 {
+                        cursor.skip_ws();
+                        if !cursor.eat(',') {
+                            return Err(
+                                cursor.error(&format!("`{}` takes {} operands", mnemonic, count))
+                            );
+                        }
+                    }
+                    let operand = cursor.operand()?;
+                    if writes && i == count - 1 && operand.mode == 1 {
+                        return Err(cursor.error_at(
+                            operand.expr.column - 1,
+                            "cannot write to an immediate operand",
+                        ));
+                    }
+                    operands.push(operand);
+                }
+                self.address += 1 + count;
+                self.items
+                    .push((Item::Instruction { opcode, operands }, self.calls.clone()));
+            }
+        }
+
+        cursor.skip_ws();
+        if !cursor.at_end() {
+            return Err(cursor.error("unexpected text"));
+        }
+        Ok(())
+    }
+
+    // Assembles the body of `def` with each parameter replaced by the matching comma separated argument in `args`
+    fn expand(
+        &mut self,
+        cursor: &Cursor,
+        start: usize,
+        name: &str,
+        def: &Macro,
+        args: &str,
+    ) -> Result<(), AsmError> {
+        let args: Vec<&str> = if args.trim().is_empty() {
+            Vec::new()
+        } else {
+            args.split(',').map(str::trim).collect()
+        };
+        if args.len() != def.params.len() || args.iter().any(|a| a.is_empty()) {
+            return Err(cursor.error_at(
+                start,
+                &format!("`{}` takes {} arguments", name, def.params.len()),
+            ));
+        }
+        if self.calls.len() >= MAX_EXPANSION_DEPTH {
+            return Err(cursor.error_at(start, "macros nested too deeply"));
+        }
+
+        let call = Call {
+            name: name.to_string(),
+            line: cursor.line,
+            column: start + 1,
+        };
+        self.calls.push(call.clone());
+        for (line, text) in &def.body {
+            let text = substitute(text, &def.params, &args);
+            if let Err(e) = self.line(*line, &text) {
+                return Err(expanded(e, &[call]));
+            }
+        }
+        self.calls.pop();
+        Ok(())
+    }
+}
+
+// Replaces every identifier in `text` that names one of `params` with the matching argument
+fn substitute(text: &str, params: &[String], args: &[&str]) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        // directives like `.data` aren't identifiers, and neither is anything following a digit
+        let after_word = out.ends_with(|c: char| c == '.' || c.is_ascii_alphanumeric() || c == '_');
+        if is_ident_start(c) && !after_word {
+            let end = rest
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                .unwrap_or(rest.len());
+            let ident = &rest[..end];
+            match params.iter().position(|p| p == ident) {
+                Some(i) => out.push_str(args[i]),
+                None => out.push_str(ident),
+            }
+            rest = &rest[end..];
+        } else {
+            out.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    out
+}
+
+enum Item {
+    Instruction { opcode: i64, operands: Vec<Operand> },
+    Data(Vec<Expr>),
+}
+
+struct Operand {
+    mode: i64,
+    expr: Expr,
+}
+
+// Sum of signed terms, each an integer or a symbol
+struct Expr {
+    line: usize,
+    column: usize,
+    terms: Vec<(i64, Term)>,
+}
+
+enum Term {
+    Num(i64),
+    Symbol(String, usize), // name and column
+}
+
+impl Expr {
+    fn eval(&self, symbols: &HashMap<String, i64>) -> Result<i64, AsmError> {
+        let mut total: i64 = 0;
+        for (sign, term) in &self.terms {
+            let val = match term {
+                Term::Num(n) => *n,
+                Term::Symbol(name, column) => match symbols.get(name) {
+                    Some(&val) => val,
+                    None => {
+                        return Err(self.error(*column, &format!("undefined symbol `{}`", name)))
+                    }
+                },
+            };
+            total = match sign.checked_mul(val).and_then(|v| total.checked_add(v)) {
+                Some(t) => t,
+                None => return Err(self.error(self.column, "value out of range")),
+            };
+        }
+        Ok(total)
+    }
+
+    fn error(&self, column: usize, message: &str) -> AsmError {
+        AsmError {
+            line: self.line,
+            column,
+            message: message.to_string(),
+        }
+    }
+}
+
+struct Cursor<'a> {
+    line: usize,
+    text: &'a str,
+    pos: usize, // byte offset into `text`
+}
+
+impl<'a> Cursor<'a> {
+    fn new(line: usize, text: &'a str) -> Self {
+        // everything after `;` is a comment
+        let text = match text.find(';') {
+            Some(i) => &text[..i],
+            None => text,
+        };
+        Self { line, text, pos: 0 }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    fn at_end(&self) -> bool {
+        self.pos >= self.text.len()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn skip_ws(&mut self) {
+        self.take_while(char::is_whitespace);
+    }
+
+    fn take_while<F: Fn(char) -> bool>(&mut self, f: F) -> &'a str {
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            if !f(c) {
+                break;
+            }
+            self.pos += c.len_utf8();
+        }
+        &self.text[start..self.pos]
+    }
+
+    fn ident(&mut self) -> &'a str {
+        match self.peek() {
+            Some(c) if is_ident_start(c) => {
+                self.take_while(|c| c.is_ascii_alphanumeric() || c == '_')
+            }
+            _ => "",
+        }
+    }
+
+    fn operand(&mut self) -> Result<Operand, AsmError> {
+        self.skip_ws();
+        let start = self.pos;
+        if self.eat('[') {
+            let expr = self.expr()?;
+            self.skip_ws();
+            if !self.eat(']') {
+                return Err(self.error("expected `]`"));
+            }
+            Ok(Operand { mode: 0, expr })
+        } else if self.eat('#') {
+            Ok(Operand {
+                mode: 1,
+                expr: self.expr()?,
+            })
+        } else if self.ident() == "rb" {
+            self.skip_ws();
+            let expr = match self.peek() {
+                Some('+') | Some('-') => self.expr()?,
+                _ => Expr {
+                    line: self.line,
+                    column: start + 1,
+                    terms: vec![(1, Term::Num(0))],
+                },
+            };
+            Ok(Operand { mode: 2, expr })
+        } else {
+            Err(self.error_at(start, "expected `[address]`, `#value` or `rb+offset`"))
+        }
+    }
+
+    fn expr(&mut self) -> Result<Expr, AsmError> {
+        self.skip_ws();
+        let column = self.pos + 1;
+        let mut terms = Vec::new();
+        let mut sign = if self.eat('-') {
+            -1
+        } else {
+            self.eat('+');
+            1
+        };
+        loop {
+            self.skip_ws();
+            let start = self.pos;
+            let term = match self.peek() {
+                Some(c) if c.is_ascii_digit() => {
+                    // parsed with its sign so `-9223372036854775808` fits
+                    let digits = self.take_while(|c| c.is_ascii_digit());
+                    let text = if sign < 0 {
+                        format!("-{}", digits)
+                    } else {
+                        digits.to_string()
+                    };
+                    sign = 1;
+                    match text.parse::<i64>() {
+                        Ok(n) => Term::Num(n),
+                        Err(_) => return Err(self.error_at(start, "value out of range")),
+                    }
+                }
+                Some(c) if is_ident_start(c) => Term::Symbol(self.ident().to_string(), start + 1),
+                _ => return Err(self.error("expected value")),
+            };
+            terms.push((sign, term));
+
+            self.skip_ws();
+            sign = if self.eat('+') {
+                1
+            } else if self.eat('-') {
+                -1
+            } else {
+                break;
+            };
+        }
+        Ok(Expr {
+            line: self.line,
+            column,
+            terms,
+        })
+    }
+
+    fn error(&self, message: &str) -> AsmError {
+        self.error_at(self.pos, message)
+    }
+
+    fn error_at(&self, pos: usize, message: &str) -> AsmError {
+        AsmError {
+            line: self.line,
+            column: pos + 1,
+            message: message.to_string(),
+        }
+    }
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use intcode::{disasm, Computer};
+
+    #[test]
+    fn quine() {
+        let src = "
+            .const COUNT 100
+            .const DONE COUNT+1
+            start:
+                arb #1
+                out rb-1
+                add [COUNT], #1, [COUNT]
+                eq [COUNT], #16, [DONE]
+                jf [DONE], #start
+                hlt
+        ";
+        let program = assemble(src).unwrap();
+        assert_eq!(
+            vec![109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99],
+            program
+        );
+
+        let mut c = Computer::new(program.clone());
+        assert_eq!(Ok(program), c.run(&[]));
+    }
+
+    #[test]
+    fn amp3() {
+        let src = "
+            in [phase]
+            in [signal]
+            mul [signal], #10, [signal]
+            add [phase], #-2, [phase]
+            lt [phase], #0, [tmp]
+            mul [tmp], #7, [tmp]
+            add [tmp], [phase], [phase]
+            add [signal], [phase], [phase]
+            out [phase]
+            hlt
+            phase: .data 0
+            signal: .data 0
+            tmp: .data 0
+        ";
+        let data = assemble(src).unwrap();
+        assert_eq!(
+            vec![
+                3, 31, 3, 32, 1002, 32, 10, 32, 1001, 31, -2, 31, 1007, 31, 0, 33, 1002, 33, 7, 33,
+                1, 33, 31, 31, 1, 32, 31, 31, 4, 31, 99, 0, 0, 0,
+            ],
+            data
+        );
+
+        let mut output = vec![0];
+        for phase in &[1, 0, 4, 3, 2] {
+            let mut amp = Computer::new(data.clone());
+            output = amp.run(&[*phase, output[0]]).unwrap();
+        }
+        assert_eq!(vec![65210], output);
+    }
+
+    #[test]
+    fn round_trip() {
+        let data = vec![
+            109,
+            1,
+            204,
+            -1,
+            1001,
+            100,
+            1,
+            100,
+            1008,
+            100,
+            16,
+            101,
+            1006,
+            101,
+            0,
+            99,
+            7,
+            -3,
+            i64::MIN,
+            i64::MAX,
+        ];
+        assert_eq!(data, assemble(&disasm::disassemble(&data)).unwrap());
+        assert_eq!(
+            Ok(vec![1101, i64::MIN, 0, 5]),
+            assemble("add #-9223372036854775808, #0, [5]")
+        );
+        assert_eq!(
+            Ok(vec![i64::MIN]),
+            assemble(".data 0 - 9223372036854775808")
+        );
+    }
+
+    #[test]
+    fn to_string() {
+        assert_eq!(
+            Ok("1101,2,3,0,99".to_string()),
+            assemble_to_string("add #2, #3, [0] ; comment\nhlt")
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let err = |src| {
+            let e = assemble(src).unwrap_err();
+            (e.line, e.column)
+        };
+
+        assert_eq!((1, 1), err("nop"));
+        assert_eq!((2, 12), err("hlt\nadd [0], #1"));
+        assert_eq!((1, 15), err("add [0], #1, #2"));
+        assert_eq!((1, 5), err("out 5"));
+        assert_eq!((1, 6), err("out [x]"));
+        assert_eq!((2, 1), err("a: hlt\na: hlt"));
+        assert_eq!((1, 5), err("hlt hlt"));
+        assert_eq!((1, 9), err(".data 1,"));
+        assert_eq!((1, 1), err("rb: hlt"));
+        assert_eq!(
+            "2:7: value out of range",
+            assemble(".const M -9223372036854775807-1\n.data -M")
+                .unwrap_err()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn macros() {
+        let src = "
+            .macro inc addr
+                add addr, #1, addr
+            .endm
+            .macro swap a, b, tmp
+                add a, #0, tmp
+                add b, #0, a
+                add tmp, #0, b
+            .endm
+            .macro start
+                in [x]
+                in [y]
+            .endm
+            start
+            inc [x]
+            swap [x], [y], rb+0
+            out [x]
+            out [y]
+            hlt
+            x: .data 0
+            y: .data 0
+        ";
+        let program = assemble(src).unwrap();
+        assert_eq!(
+            vec![
+                3, 25, 3, 26, 1001, 25, 1, 25, 21001, 25, 0, 0, 1001, 26, 0, 25, 1201, 0, 0, 26, 4,
+                25, 4, 26, 99, 0, 0,
+            ],
+            program
+        );
+        assert_eq!(Ok(vec![7, 3]), Computer::new(program).run(&[2, 7]));
+
+        // parameters aren't replaced inside directives or other identifiers
+        assert_eq!(
+            Ok(vec![1, 5]),
+            assemble(".macro d data\n.data data, data_x\n.endm\n.const data_x 5\nd 1")
+        );
+    }
+
+    #[test]
+    fn macro_errors() {
+        let err = |src| assemble(src).unwrap_err().to_string();
+
+        // reported at the invocation, with the position in the expanded body line
+        assert_eq!(
+            "4:1: in macro `m` at 2:14: cannot write to an immediate operand",
+            err(".macro m a\nadd #1, #1, a\n.endm\nm #5")
+        );
+        assert_eq!(
+            "7:1: in macro `outer` at 5:1: in macro `inner` at 2:6: undefined symbol `x`",
+            err(".macro inner\nout [x]\n.endm\n.macro outer\ninner\n.endm\nouter")
+        );
+        assert_eq!(
+            "3:1: `m` takes 2 arguments",
+            err(".macro m a, b\n.endm\nm 1")
+        );
+        assert_eq!("1:1: `.macro m` without `.endm`", err(".macro m\nhlt"));
+        assert_eq!("1:1: `.endm` without `.macro`", err(".endm"));
+        assert_eq!("1:8: duplicate instruction `add`", err(".macro add\n.endm"));
+        assert!(err(".macro m\nm\n.endm\nm").ends_with("2:1: macros nested too deeply"));
+    }
+}
@@ -0,0 +1,15 @@
+fn main() {
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "input.asm".to_string());
+
+    let src = match std::fs::read_to_string(&path) {
+        Ok(s) => s,
+        Err(e) => panic!("Error reading {} {}", path, e),
+    };
+
+    match intcode_asm::assemble_to_string(&src) {
+        Ok(program) => println!("{}", program),
+        Err(e) => panic!("Error assembling {}:{}", path, e),
+    }
+}